@@ -0,0 +1,329 @@
+//! High scores of past games, persisted to a local JSON file.
+//!
+//! The file carries a `version` so that older binaries refuse files written by newer ones
+//! instead of silently dropping fields, and every save replaces it atomically. Scores are
+//! recorded under a lock file, so games that end at the same time all keep their score.
+use std::{
+    error::Error,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Difficulty;
+
+/// Version of the file format written by this build
+pub const FORMAT_VERSION: u32 = 1;
+
+/// One won game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    pub attempts: u32,
+    #[serde(with = "millis")]
+    pub duration: Duration,
+    pub difficulty: Difficulty,
+    pub date: DateTime<Utc>,
+}
+
+impl Score {
+    /// Score of a game won now.
+    pub fn new(name: &str, attempts: u32, duration: Duration, difficulty: Difficulty) -> Self {
+        Self {
+            name: name.to_string(),
+            attempts,
+            duration,
+            difficulty,
+            date: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LeaderboardError {
+    Io(io::Error),
+    /// The file exists but is not a valid leaderboard
+    Parse(serde_json::Error),
+    /// The file was written by a newer version of the game
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for LeaderboardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeaderboardError::Io(err) => write!(f, "could not access the leaderboard: {}", err),
+            LeaderboardError::Parse(err) => write!(f, "invalid leaderboard file: {}", err),
+            LeaderboardError::UnsupportedVersion(version) => write!(
+                f,
+                "leaderboard file version {} is newer than the supported version {}",
+                version, FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for LeaderboardError {}
+
+impl From<io::Error> for LeaderboardError {
+    fn from(err: io::Error) -> Self {
+        LeaderboardError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LeaderboardError {
+    fn from(err: serde_json::Error) -> Self {
+        LeaderboardError::Parse(err)
+    }
+}
+
+/// Only the version, read before the rest so a newer format is reported as such
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct File<S> {
+    version: u32,
+    scores: S,
+}
+
+/// Scores kept in the file at `path`.
+#[derive(Debug)]
+pub struct Leaderboard {
+    path: PathBuf,
+    scores: Vec<Score>,
+}
+
+impl Leaderboard {
+    /// Reads the leaderboard at `path`; a missing file is an empty leaderboard.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, LeaderboardError> {
+        let path = path.into();
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self {
+                    path,
+                    scores: Vec::new(),
+                })
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let header: Header = serde_json::from_slice(&contents)?;
+        if header.version > FORMAT_VERSION {
+            return Err(LeaderboardError::UnsupportedVersion(header.version));
+        }
+        let file: File<Vec<Score>> = serde_json::from_slice(&contents)?;
+
+        Ok(Self {
+            path,
+            scores: file.scores,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn add(&mut self, score: Score) {
+        self.scores.push(score);
+    }
+
+    /// Adds `score` to the leaderboard at `path`, reading and saving the file while holding
+    /// a lock on `path.lock`, so a score saved by another game in the meantime is kept.
+    pub fn record(path: impl Into<PathBuf>, score: Score) -> Result<(), LeaderboardError> {
+        let path = path.into();
+        create_parent_dir(&path)?;
+        let lock = fs::File::create(sibling_path(&path, ".lock")?)?;
+        lock.lock()?;
+
+        let mut leaderboard = Self::load(path)?;
+        leaderboard.add(score);
+        leaderboard.save()
+    }
+
+    /// The best `n` scores of `difficulty`: fewest attempts first, then fastest.
+    pub fn top(&self, difficulty: Difficulty, n: usize) -> Vec<&Score> {
+        let mut scores: Vec<_> = self
+            .scores
+            .iter()
+            .filter(|score| score.difficulty == difficulty)
+            .collect();
+        scores.sort_by_key(|score| (score.attempts, score.duration));
+        scores.truncate(n);
+        scores
+    }
+
+    /// Writes the leaderboard to a temporary file next to `path` and renames it over `path`,
+    /// so a crash in the middle of a save never leaves a truncated file behind.
+    pub fn save(&self) -> Result<(), LeaderboardError> {
+        create_parent_dir(&self.path)?;
+        let temp = sibling_path(&self.path, &format!(".{}.tmp", process::id()))?;
+
+        let result = (|| {
+            let mut file = fs::File::create(&temp)?;
+            serde_json::to_writer_pretty(
+                &mut file,
+                &File {
+                    version: FORMAT_VERSION,
+                    scores: &self.scores,
+                },
+            )?;
+            writeln!(file)?;
+            file.sync_all()?;
+            fs::rename(&temp, &self.path)?;
+            Ok(())
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    /// Prints the best `n` scores of each difficulty.
+    pub fn print<W: Write>(&self, output: &mut W, n: usize) -> io::Result<()> {
+        for difficulty in Difficulty::ALL {
+            writeln!(output, "{:?}", difficulty)?;
+            let top = self.top(difficulty, n);
+            if top.is_empty() {
+                writeln!(output, "  no scores yet")?;
+            }
+            for (position, score) in top.iter().enumerate() {
+                writeln!(
+                    output,
+                    "  {}. {} - {} attempts in {:.1}s on {}",
+                    position + 1,
+                    score.name,
+                    score.attempts,
+                    score.duration.as_secs_f64(),
+                    score.date.format("%Y-%m-%d")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn create_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        Some(dir) => fs::create_dir_all(dir),
+        None => Ok(()),
+    }
+}
+
+/// `path` with `suffix` appended to its file name
+fn sibling_path(path: &Path, suffix: &str) -> io::Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("the leaderboard path has no file name"))?;
+    let mut name = file_name.to_os_string();
+    name.push(suffix);
+    Ok(path.with_file_name(name))
+}
+
+/// Serializes a [`Duration`] as whole milliseconds
+mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("guessing_game-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("scores.json")
+    }
+
+    fn score(name: &str, attempts: u32, secs: u64, difficulty: Difficulty) -> Score {
+        Score::new(name, attempts, Duration::from_secs(secs), difficulty)
+    }
+
+    #[test]
+    fn should_start_empty_without_file() {
+        let leaderboard = Leaderboard::load(temp_path("empty")).unwrap();
+
+        assert!(leaderboard.top(Difficulty::Easy, 10).is_empty());
+    }
+
+    #[test]
+    fn should_save_and_load_scores() {
+        let path = temp_path("round-trip");
+        let mut leaderboard = Leaderboard::load(&path).unwrap();
+        leaderboard.add(score("ana", 7, 30, Difficulty::Medium));
+        leaderboard.add(score("bia", 4, 90, Difficulty::Medium));
+        leaderboard.add(score("caio", 4, 20, Difficulty::Medium));
+        leaderboard.add(score("davi", 1, 1, Difficulty::Easy));
+        leaderboard.save().unwrap();
+
+        let loaded = Leaderboard::load(&path).unwrap();
+        let names: Vec<_> = loaded
+            .top(Difficulty::Medium, 2)
+            .iter()
+            .map(|score| score.name.as_str())
+            .collect();
+
+        assert_eq!(vec!["caio", "bia"], names);
+        assert_eq!(leaderboard.scores, loaded.scores);
+        assert_eq!(1, fs::read_dir(path.parent().unwrap()).unwrap().count());
+    }
+
+    #[test]
+    fn should_keep_scores_recorded_at_the_same_time() {
+        let path = temp_path("concurrent");
+
+        std::thread::scope(|scope| {
+            for attempts in 1..=8 {
+                let path = &path;
+                scope.spawn(move || {
+                    Leaderboard::record(path, score("ana", attempts, 10, Difficulty::Easy)).unwrap()
+                });
+            }
+        });
+
+        let loaded = Leaderboard::load(&path).unwrap();
+        assert_eq!(8, loaded.top(Difficulty::Easy, 10).len());
+    }
+
+    #[test]
+    fn should_refuse_newer_versions() {
+        let path = temp_path("newer");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, r#"{"version": 2, "scores": {"easy": []}}"#).unwrap();
+
+        assert!(matches!(
+            Leaderboard::load(&path),
+            Err(LeaderboardError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn should_print_top_scores_per_difficulty() {
+        let mut leaderboard = Leaderboard::load(temp_path("print")).unwrap();
+        leaderboard.add(score("ana", 3, 12, Difficulty::Easy));
+        let mut output = Vec::new();
+
+        leaderboard.print(&mut output, 5).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Easy\n  1. ana - 3 attempts in 12.0s on "));
+        assert!(output.ends_with("Medium\n  no scores yet\nHard\n  no scores yet\n"));
+    }
+}
@@ -1,68 +1,216 @@
-///! A game of guessing a number randomly generated by code.
-///! The software should print and allow the user to enter numbers;
-///! The software should generate a number randomly between 1 and 1000;
-///! The software should print to player whether the number inserted is lower, higher or equal than the random number;
-///! The software should exit when the player guesses the number or when they enter any value other than the number.
+//! A game of guessing a number randomly generated by code.
+//! The software should print and allow the user to enter numbers;
+//! The software should generate a number randomly in the range of the chosen [`Difficulty`];
+//! The software should print to player whether the number inserted is lower, higher or equal than the random number;
+//! The software should exit when the player guesses the number, when they run out of attempts, when they type `quit` or when the input ends;
+//! Anything other than a number in the range is answered with a retry message, and `hint` shows where the number is.
+mod leaderboard;
+mod server;
+mod settings;
+mod solver;
+
 use std::{
     cmp::Ordering,
     io::{stdin, stdout, BufRead, BufReader, Error, Write},
+    ops::RangeInclusive,
+    time::{Duration, Instant},
 };
 
+pub use leaderboard::{Leaderboard, LeaderboardError, Score, FORMAT_VERSION};
+pub use server::{Server, Winner};
+pub use settings::{Difficulty, Settings};
+pub use solver::{simulate, solve, Simulation, Solver, Strategy};
+
+// Messages that the [`Solver`] reads back to follow the game
+const PROMPT: &str = "Please, enter a guess number";
+const LOWER: &str = "Guess number is lower 👇🏽";
+const HIGHER: &str = "Guess number is higher ☝🏽";
+
+/// How a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player guessed the secret on the `attempts`-th guess, `duration` after the game
+    /// started
+    Won { attempts: u32, duration: Duration },
+    /// The player used every attempt without guessing the secret
+    Lost,
+    /// The player typed `quit` or the input ended before the game did
+    Quit,
+}
+
+/// A line typed by the player
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Guess(u32),
+    Hint,
+    Quit,
+    Empty,
+    Invalid(String),
+}
+
+/// The guesses of one player: how many were made and what they revealed about the secret
+struct Guesses {
+    range: RangeInclusive<u32>,
+    /// Smallest and largest numbers the secret can still be, shown by `hint`
+    low: u32,
+    high: u32,
+    attempt: u32,
+    max_attempts: u32,
+}
+
+impl Guesses {
+    fn new(settings: &Settings) -> Self {
+        Self {
+            range: settings.range.clone(),
+            low: *settings.range.start(),
+            high: *settings.range.end(),
+            attempt: 1,
+            max_attempts: settings.max_attempts,
+        }
+    }
+
+    fn exhausted(&self) -> bool {
+        self.attempt > self.max_attempts
+    }
+
+    /// Prompts until the player types a number in the range, answering anything else with a
+    /// retry message or a hint. Returns `None` when the player quits.
+    fn ask<R, W>(&self, reader: &mut R, writer: &mut W) -> Result<Option<u32>, Error>
+    where
+        R: BufRead + ?Sized,
+        W: Write + ?Sized,
+    {
+        loop {
+            writeln!(
+                writer,
+                "{} (attempt {} of {})",
+                PROMPT, self.attempt, self.max_attempts
+            )?;
+            writer.flush()?;
+
+            match Config::read_command(reader)? {
+                Command::Guess(guess) if self.range.contains(&guess) => return Ok(Some(guess)),
+                Command::Guess(guess) => writeln!(
+                    writer,
+                    "{} is out of range, the number is between {} and {}.",
+                    guess,
+                    self.range.start(),
+                    self.range.end()
+                )?,
+                Command::Hint => writeln!(
+                    writer,
+                    "The number is between {} and {}.",
+                    self.low, self.high
+                )?,
+                Command::Empty => writeln!(writer, "Type a number, `hint` or `quit`.")?,
+                Command::Invalid(input) => writeln!(
+                    writer,
+                    "\"{}\" is not a number. Type a number, `hint` or `quit`.",
+                    input
+                )?,
+                Command::Quit => return Ok(None),
+            }
+        }
+    }
+
+    /// Counts a wrong `guess` and narrows where the secret can be.
+    fn record(&mut self, guess: u32, secret: u32) {
+        if guess < secret {
+            self.low = self.low.max(guess + 1);
+        } else {
+            self.high = self.high.min(guess - 1);
+        }
+        self.attempt += 1;
+    }
+}
+
 pub struct Config {
-    secret: u32,
+    settings: Settings,
     pub reader: Option<Box<dyn BufRead>>,
     pub writer: Option<Box<dyn Write>>,
 }
 
 impl Config {
     pub fn new(
-        secret: u32,
+        settings: Settings,
         reader: Option<Box<dyn BufRead>>,
         writer: Option<Box<dyn Write>>,
     ) -> Self {
         let reader = reader.or_else(|| Some(Box::new(BufReader::new(stdin()))));
         let writer = writer.or_else(|| Some(Box::new(stdout())));
         Self {
-            secret,
+            settings,
             reader,
             writer,
         }
     }
 
-    pub fn run(&mut self) -> Result<(), Error> {
-        loop {
-            println!("Please, enter a guess number");
-            let mut buf_reader = self.reader.as_mut().unwrap();
-            let guess = Config::match_number(&mut buf_reader)?;
-            let mut buf_write = self.writer.as_mut().unwrap();
-            if Config::print_result(&self.secret, &mut buf_write, &guess)? {
-                break Ok(());
+    /// Asks for guesses until the player finds the secret, runs out of attempts or quits.
+    ///
+    /// Invalid, empty and out-of-range input is answered with a retry message and does not
+    /// count as an attempt. Every message goes to the writer.
+    pub fn run(&mut self) -> Result<Outcome, Error> {
+        let started = Instant::now();
+        let reader = self.reader.as_mut().unwrap();
+        let writer = self.writer.as_mut().unwrap();
+        let secret = self.settings.secret;
+        let mut guesses = Guesses::new(&self.settings);
+
+        while !guesses.exhausted() {
+            let Some(guess) = guesses.ask(reader, writer)? else {
+                writeln!(writer, "Bye! The number was {}.", secret)?;
+                return Ok(Outcome::Quit);
+            };
+
+            if Config::print_result(&secret, writer, &guess)? {
+                writeln!(writer, "You found it in {} attempts.", guesses.attempt)?;
+                return Ok(Outcome::Won {
+                    attempts: guesses.attempt,
+                    duration: started.elapsed(),
+                });
             }
+            guesses.record(guess, secret);
         }
+
+        writeln!(writer, "You lose 😞! The number was {}.", secret)?;
+        Ok(Outcome::Lost)
     }
-    fn match_number<T: BufRead>(read: &mut T) -> Result<u32, Error> {
-        let mut guess = String::new();
-        read.read_line(&mut guess)?;
 
-        guess
-            .trim()
-            .parse::<u32>()
-            .map_err(|err| Error::new(std::io::ErrorKind::Other, err))
+    /// Reads the next line typed by the player; the end of the input is a [`Command::Quit`].
+    fn read_command<T: BufRead + ?Sized>(read: &mut T) -> Result<Command, Error> {
+        let mut line = String::new();
+        if read.read_line(&mut line)? == 0 {
+            return Ok(Command::Quit);
+        }
+
+        let line = line.trim();
+        Ok(match line.to_lowercase().as_str() {
+            "" => Command::Empty,
+            "hint" => Command::Hint,
+            "quit" | "exit" => Command::Quit,
+            _ => line
+                .parse()
+                .map_or_else(|_| Command::Invalid(line.to_string()), Command::Guess),
+        })
     }
 
-    fn print_result<T: Write>(secret: &u32, output: &mut T, guess: &u32) -> Result<bool, Error> {
-        match guess.cmp(&secret) {
+    fn print_result<T: Write + ?Sized>(
+        secret: &u32,
+        output: &mut T,
+        guess: &u32,
+    ) -> Result<bool, Error> {
+        match guess.cmp(secret) {
             Ordering::Equal => {
-                write!(output, "You win 🎉!\n")?;
+                writeln!(output, "You win 🎉!")?;
                 Ok(true)
             }
             Ordering::Less => {
-                write!(output, "Guess number is lower 👇🏽\n")?;
+                writeln!(output, "{}", LOWER)?;
                 Ok(false)
             }
 
             Ordering::Greater => {
-                write!(output, "Guess number is higher ☝🏽\n")?;
+                writeln!(output, "{}", HIGHER)?;
                 Ok(false)
             }
         }
@@ -71,33 +219,131 @@ impl Config {
 
 #[cfg(test)]
 mod test {
-    use std::io::ErrorKind;
+    use std::cell::RefCell;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+
+    use crate::{Command, Config, Difficulty, Outcome, Settings};
+
+    fn play(settings: Settings, input: &'static str) -> (Outcome, String) {
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let mut config = Config::new(
+            settings,
+            Some(Box::new(input.as_bytes())),
+            Some(Box::new(Shared(output.clone()))),
+        );
 
-    use crate::Config;
+        let outcome = config.run().expect("should play until the end");
+        drop(config);
+        let output = String::from_utf8(output.take()).unwrap();
+        (outcome, output)
+    }
+
+    /// Writer that keeps the output readable after the [`Config`] takes it
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
 
     #[test]
     fn shoud_match_number() {
         let buf = "32";
 
-        let r = Config::match_number(&mut buf.as_ref()).expect("should parser a valid u32 number");
-        assert_eq!(32, r)
+        let r = Config::read_command(&mut buf.as_ref()).expect("should read the line");
+        assert_eq!(Command::Guess(32), r)
     }
 
     #[test]
     fn shoud_match_number_not() {
-        let buf = "teste";
+        let buf = "teste\n";
 
-        let r = Config::match_number(&mut buf.as_ref());
+        let r = Config::read_command(&mut buf.as_ref()).expect("should read the line");
 
-        assert_eq!(r.err().unwrap().kind(), ErrorKind::Other)
+        assert_eq!(Command::Invalid("teste".to_string()), r)
+    }
+
+    #[test]
+    fn should_read_commands() {
+        let read = |buf: &str| Config::read_command(&mut buf.as_bytes()).unwrap();
+
+        assert_eq!(Command::Hint, read(" Hint\n"));
+        assert_eq!(Command::Quit, read("quit\n"));
+        assert_eq!(Command::Quit, read(""));
+        assert_eq!(Command::Empty, read("\n"));
+        assert_eq!(Command::Invalid("-3".to_string()), read("-3\n"));
     }
 
     #[test]
     fn shoud_print_result() {
-        let config = Config::new(10, None, None);
+        let config = Config::new(Settings::new(Difficulty::Easy, 10), None, None);
         let mut binding = vec![];
 
-        let r = Config::print_result(&config.secret, &mut binding, &32);
+        let r = Config::print_result(&config.settings.secret, &mut binding, &32);
         assert!(r.is_ok())
     }
+
+    #[test]
+    fn should_use_difficulty_presets() {
+        let settings = Settings::new(Difficulty::Hard, 9999);
+
+        assert_eq!(1..=10_000, settings.range);
+        assert_eq!(14, settings.max_attempts);
+        assert!(Difficulty::Easy
+            .range()
+            .contains(&Settings::random(Difficulty::Easy).secret));
+    }
+
+    #[test]
+    fn should_count_attempts_on_win() {
+        let (outcome, output) = play(Settings::new(Difficulty::Easy, 42), "50\n30\n42\n");
+
+        assert!(matches!(outcome, Outcome::Won { attempts: 3, .. }));
+        assert!(output.ends_with("You win 🎉!\nYou found it in 3 attempts.\n"));
+    }
+
+    #[test]
+    fn should_lose_when_attempts_run_out() {
+        let settings = Settings {
+            max_attempts: 2,
+            ..Settings::new(Difficulty::Easy, 42)
+        };
+
+        let (outcome, output) = play(settings, "50\n30\n42\n");
+
+        assert_eq!(Outcome::Lost, outcome);
+        assert!(output.ends_with("You lose 😞! The number was 42.\n"));
+    }
+
+    #[test]
+    fn should_retry_on_bad_input_without_spending_attempts() {
+        let settings = Settings {
+            max_attempts: 2,
+            ..Settings::new(Difficulty::Easy, 42)
+        };
+
+        let (outcome, output) = play(settings, "abc\n\n500\n50\nhint\n42\n");
+
+        assert!(matches!(outcome, Outcome::Won { attempts: 2, .. }));
+        assert!(output.contains("\"abc\" is not a number. Type a number, `hint` or `quit`.\n"));
+        assert!(output.contains("Type a number, `hint` or `quit`.\n"));
+        assert!(output.contains("500 is out of range, the number is between 1 and 100.\n"));
+        assert!(output.contains("The number is between 1 and 49.\n"));
+    }
+
+    #[test]
+    fn should_quit_on_command_or_end_of_input() {
+        let (outcome, output) = play(Settings::new(Difficulty::Easy, 42), "10\nquit\n20\n");
+        assert_eq!(Outcome::Quit, outcome);
+        assert!(output.ends_with("Bye! The number was 42.\n"));
+
+        let (outcome, _) = play(Settings::new(Difficulty::Easy, 42), "10\n");
+        assert_eq!(Outcome::Quit, outcome);
+    }
 }
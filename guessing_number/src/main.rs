@@ -1,14 +1,112 @@
-use guessing_game::Config;
-use rand::Rng;
-use std::process;
+use clap::{builder::RangedU64ValueParser, Parser};
+use guessing_game::{Config, Difficulty, Leaderboard, Outcome, Score, Server, Settings, Strategy};
+use std::{env, error::Error, io, path::PathBuf, process};
+
+/// Guess the number drawn by the computer
+#[derive(Parser)]
+#[command(version)]
+struct Args {
+    /// Size of the range and number of attempts
+    #[arg(short, long, value_enum, default_value_t)]
+    difficulty: Difficulty,
+
+    /// Name recorded in the leaderboard when you win [default: $USER]
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Shows the best scores of each difficulty instead of playing
+    #[arg(long)]
+    scores: bool,
+
+    /// How many scores of each difficulty `--scores` shows
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+
+    /// Leaderboard file [default: $HOME/.guessing_game/scores.json]
+    #[arg(long)]
+    scores_file: Option<PathBuf>,
+
+    /// Lets the solver play this many games and shows how many attempts it needed
+    #[arg(long, value_name = "GAMES")]
+    simulate: Option<usize>,
+
+    /// How the solver picks its guesses in `--simulate`
+    #[arg(long, value_enum, default_value_t)]
+    strategy: Strategy,
+
+    /// Seed of the secrets and guesses drawn in `--simulate`
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Hosts multiplayer rounds on this address (like 127.0.0.1:7878) instead of playing
+    #[arg(long, value_name = "ADDR")]
+    serve: Option<String>,
+
+    /// Players that must join before each round of `--serve` starts
+    #[arg(
+        long,
+        default_value_t = 2,
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    players: usize,
+}
 
 fn main() {
-    println!("Adivinha um número");
-    let secret = rand::thread_rng().gen_range(1, 1001);
+    let args = Args::parse();
 
-    if let Err(err) = Config::new(secret, None, None).run() {
+    if let Err(err) = play(args) {
         eprintln!("{}", err);
 
         process::exit(1)
     }
 }
+
+fn play(args: Args) -> Result<(), Box<dyn Error>> {
+    if let Some(games) = args.simulate {
+        let simulation = guessing_game::simulate(args.difficulty, args.strategy, games, args.seed)?;
+        simulation.print(&mut io::stdout())?;
+        return Ok(());
+    }
+
+    if let Some(addr) = &args.serve {
+        let server = Server::bind(addr)?;
+        println!("Waiting for players on {}", server.local_addr()?);
+        loop {
+            let settings = Settings::random(args.difficulty);
+            match server.play_round(&settings, args.players)? {
+                Some(winner) => println!("{} won with {} attempts", winner.name, winner.attempts),
+                None => println!("Nobody won"),
+            }
+        }
+    }
+
+    let scores_file = args.scores_file.unwrap_or_else(default_scores_file);
+    if args.scores {
+        Leaderboard::load(scores_file)?.print(&mut io::stdout(), args.top)?;
+        return Ok(());
+    }
+
+    println!("Adivinha um número");
+    let settings = Settings::random(args.difficulty);
+
+    if let Outcome::Won { attempts, duration } = Config::new(settings, None, None).run()? {
+        let name = args
+            .name
+            .or_else(|| env::var("USER").ok())
+            .unwrap_or_else(|| "anonymous".to_string());
+        let score = Score::new(&name, attempts, duration, args.difficulty);
+        // Read only now, so scores saved by other games in the meantime are kept
+        Leaderboard::record(&scores_file, score)
+            .map_err(|err| format!("could not save your score: {}", err))?;
+    }
+
+    Ok(())
+}
+
+fn default_scores_file() -> PathBuf {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".guessing_game")
+        .join("scores.json")
+}
@@ -0,0 +1,372 @@
+//! Multiplayer rounds over TCP: every player guesses the same secret and the first to find
+//! it wins.
+//!
+//! Each connection is played with the same prompts and messages as the single player
+//! [`Config`](crate::Config), through a [`BufRead`] over the socket and a [`Write`] to it, so
+//! any client that talks to the game (like `nc` or the [`Solver`](crate::Solver)) works.
+//!
+//! Connections are accepted in the background and each one asks for the player's name in
+//! its own thread, so a client that never answers or drops the connection in the lobby
+//! only loses its own seat.
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, Receiver},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{Config, Guesses, Settings};
+
+/// The player who found the secret first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Winner {
+    pub name: String,
+    pub attempts: u32,
+}
+
+/// How long a new connection has to send the player's name before it is dropped
+const NAME_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct Server {
+    addr: SocketAddr,
+    /// Players who sent their name, in the order they did, waiting for a round
+    lobby: Mutex<Receiver<Joined>>,
+}
+
+/// A connection that already sent the player's name
+struct Joined {
+    name: String,
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Server {
+    /// Listens on `addr` and starts accepting players in a background thread, which keeps
+    /// running until the program ends.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let (lobby, joined) = mpsc::channel();
+
+        thread::spawn(move || {
+            // A connection that fails before joining never reaches a round
+            for stream in listener.incoming().filter_map(Result::ok) {
+                let lobby = lobby.clone();
+                thread::spawn(move || {
+                    if let Ok(player) = greet(stream) {
+                        let _ = lobby.send(player);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            addr,
+            lobby: Mutex::new(joined),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.addr)
+    }
+
+    /// Waits for `players` to join and plays one round of `settings` with them. Players who
+    /// join during a round wait for the next one.
+    ///
+    /// Returns when someone finds the secret or every player has quit or run out of
+    /// attempts; the result is sent to everyone and the connections are closed.
+    pub fn play_round(&self, settings: &Settings, players: usize) -> io::Result<Option<Winner>> {
+        let mut table = Table {
+            names: Vec::new(),
+            streams: Vec::new(),
+            state: Mutex::new(State {
+                winner: None,
+                playing: players,
+            }),
+        };
+        let mut readers = Vec::new();
+        let lobby = self.lobby.lock().unwrap();
+
+        while readers.len() < players {
+            let Joined {
+                name,
+                stream,
+                reader,
+            } = lobby
+                .recv()
+                .map_err(|_| io::Error::other("the server stopped accepting players"))?;
+            let name = match name.as_str() {
+                "" => format!("Player {}", readers.len() + 1),
+                _ => name,
+            };
+
+            table.names.push(name);
+            table.streams.push(Mutex::new(stream));
+            readers.push(reader);
+            table.broadcast(&format!(
+                "{} joined ({} of {}).",
+                table.names[readers.len() - 1],
+                readers.len(),
+                players
+            ));
+        }
+
+        table.broadcast(&format!(
+            "The round starts! The number is between {} and {} and each player has {} attempts.",
+            settings.range.start(),
+            settings.range.end(),
+            settings.max_attempts
+        ));
+        thread::scope(|scope| {
+            for (player, mut reader) in readers.into_iter().enumerate() {
+                let table = &table;
+                scope.spawn(move || {
+                    // A player who disconnects is just out of the round
+                    let _ = table.play(settings, player, &mut reader);
+                    table.leave(settings);
+                });
+            }
+        });
+
+        Ok(table.state.into_inner().unwrap().winner)
+    }
+}
+
+/// Welcomes a new connection and reads the player's name.
+///
+/// Fails when the client disconnects or takes longer than [`NAME_TIMEOUT`] to answer.
+fn greet(mut stream: TcpStream) -> io::Result<Joined> {
+    // Messages are short lines that should arrive as soon as they are written
+    stream.set_nodelay(true)?;
+    writeln!(stream, "Welcome! What is your name?")?;
+    stream.set_read_timeout(Some(NAME_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut name = String::new();
+    if reader.read_line(&mut name)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    // The guesses may take as long as the player wants
+    stream.set_read_timeout(None)?;
+
+    Ok(Joined {
+        name: name.trim().to_string(),
+        stream,
+        reader,
+    })
+}
+
+/// The players of a round and what happened so far
+struct Table {
+    names: Vec<String>,
+    streams: Vec<Mutex<TcpStream>>,
+    state: Mutex<State>,
+}
+
+struct State {
+    winner: Option<Winner>,
+    /// Players still guessing
+    playing: usize,
+}
+
+impl Table {
+    /// Plays the guesses of `player` until they win, quit or run out of attempts.
+    fn play<R: BufRead>(
+        &self,
+        settings: &Settings,
+        player: usize,
+        reader: &mut R,
+    ) -> io::Result<()> {
+        let mut seat = Seat {
+            table: self,
+            player,
+            line: Vec::new(),
+        };
+        let mut guesses = Guesses::new(settings);
+
+        while !guesses.exhausted() {
+            let Some(guess) = guesses.ask(reader, &mut seat)? else {
+                return Ok(());
+            };
+
+            let mut state = self.state.lock().unwrap();
+            if state.winner.is_some() {
+                return Ok(());
+            }
+            if Config::print_result(&settings.secret, &mut seat, &guess)? {
+                let winner = Winner {
+                    name: self.names[player].clone(),
+                    attempts: guesses.attempt,
+                };
+                self.broadcast(&format!(
+                    "{} won with {} attempts! The number was {}.",
+                    winner.name, winner.attempts, settings.secret
+                ));
+                state.winner = Some(winner);
+                self.close();
+                return Ok(());
+            }
+            drop(state);
+            guesses.record(guess, settings.secret);
+        }
+
+        writeln!(seat, "You are out of attempts, wait for the others.")
+    }
+
+    /// Ends the round when the last player still guessing leaves without a winner.
+    fn leave(&self, settings: &Settings) {
+        let mut state = self.state.lock().unwrap();
+        state.playing -= 1;
+        if state.playing == 0 && state.winner.is_none() {
+            self.broadcast(&format!("Nobody won! The number was {}.", settings.secret));
+            self.close();
+        }
+    }
+
+    fn send(&self, player: usize, message: &[u8]) -> io::Result<()> {
+        self.streams[player].lock().unwrap().write_all(message)
+    }
+
+    /// Sends `message` to every player still connected.
+    fn broadcast(&self, message: &str) {
+        let line = format!("{}\n", message);
+        for player in 0..self.streams.len() {
+            let _ = self.send(player, line.as_bytes());
+        }
+    }
+
+    /// Closes every connection, which also ends the reads of the players still guessing.
+    fn close(&self) {
+        for stream in &self.streams {
+            let _ = stream.lock().unwrap().shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Writes to one player a whole line at a time, so that broadcasts from other threads never
+/// land in the middle of a message.
+struct Seat<'a> {
+    table: &'a Table,
+    player: usize,
+    line: Vec<u8>,
+}
+
+impl Write for Seat<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.line.extend_from_slice(buf);
+        if let Some(end) = self.line.iter().rposition(|byte| *byte == b'\n') {
+            let rest = self.line.split_off(end + 1);
+            self.table.send(self.player, &self.line)?;
+            self.line = rest;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{Difficulty, Solver, Strategy};
+
+    fn join(addr: SocketAddr, name: &str) -> TcpStream {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        writeln!(stream, "{}", name).unwrap();
+        stream
+    }
+
+    #[test]
+    fn should_broadcast_first_correct_guess() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let settings = Settings::new(Difficulty::Medium, 777);
+
+        thread::scope(|scope| {
+            let round = scope.spawn(|| server.play_round(&settings, 2));
+
+            let waiting = join(addr, "bia");
+            let mut waiting = BufReader::new(waiting);
+            let mut lobby = String::new();
+            waiting.read_line(&mut lobby).unwrap();
+            waiting.read_line(&mut lobby).unwrap();
+            assert_eq!("Welcome! What is your name?\nbia joined (1 of 2).\n", lobby);
+            let ana = join(addr, "ana");
+            Solver::new(Strategy::Binary, StdRng::seed_from_u64(1))
+                .play((1, 1000), BufReader::new(ana.try_clone().unwrap()), ana)
+                .unwrap();
+
+            let winner = round.join().unwrap().unwrap();
+            assert_eq!(
+                Some(Winner {
+                    name: "ana".to_string(),
+                    attempts: 8
+                }),
+                winner
+            );
+
+            let mut output = String::new();
+            waiting.read_to_string(&mut output).unwrap();
+            assert!(output.contains("ana won with 8 attempts! The number was 777.\n"));
+        });
+    }
+
+    #[test]
+    fn should_skip_clients_that_never_join() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let settings = Settings::new(Difficulty::Easy, 42);
+
+        thread::scope(|scope| {
+            let round = scope.spawn(|| server.play_round(&settings, 2));
+
+            let _silent = TcpStream::connect(addr).unwrap();
+            drop(TcpStream::connect(addr).unwrap());
+            let mut ana = join(addr, "ana");
+            let _bia = join(addr, "bia");
+            writeln!(ana, "42").unwrap();
+
+            assert_eq!(
+                Some(Winner {
+                    name: "ana".to_string(),
+                    attempts: 1
+                }),
+                round.join().unwrap().unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn should_end_round_when_everyone_leaves() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let settings = Settings {
+            max_attempts: 1,
+            ..Settings::new(Difficulty::Easy, 42)
+        };
+
+        thread::scope(|scope| {
+            let round = scope.spawn(|| server.play_round(&settings, 2));
+
+            let mut ana = join(addr, "ana");
+            let mut bia = join(addr, "bia");
+            writeln!(ana, "10").unwrap();
+            writeln!(bia, "quit").unwrap();
+
+            assert_eq!(None, round.join().unwrap().unwrap());
+            let mut output = String::new();
+            ana.read_to_string(&mut output).unwrap();
+            assert!(output.contains("You are out of attempts, wait for the others.\n"));
+            assert!(output.ends_with("Nobody won! The number was 42.\n"));
+        });
+    }
+}
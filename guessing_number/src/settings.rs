@@ -0,0 +1,72 @@
+//! Difficulty presets and the settings of a single game.
+use std::ops::RangeInclusive;
+
+use clap::ValueEnum;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Preset that defines how large the range of the secret number is and how many guesses the
+/// player has to find it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// Between 1 and 100, with 10 attempts
+    Easy,
+    /// Between 1 and 1000, with 10 attempts
+    #[default]
+    Medium,
+    /// Between 1 and 10000, with 14 attempts, enough for a binary search
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn range(self) -> RangeInclusive<u32> {
+        match self {
+            Difficulty::Easy => 1..=100,
+            Difficulty::Medium => 1..=1000,
+            Difficulty::Hard => 1..=10_000,
+        }
+    }
+
+    pub fn max_attempts(self) -> u32 {
+        match self {
+            Difficulty::Easy | Difficulty::Medium => 10,
+            Difficulty::Hard => 14,
+        }
+    }
+}
+
+/// Everything a [`Config`](crate::Config) needs to play one game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub secret: u32,
+    /// Range the secret number was drawn from
+    pub range: RangeInclusive<u32>,
+    /// Guesses allowed before the player loses
+    pub max_attempts: u32,
+    pub difficulty: Difficulty,
+}
+
+impl Settings {
+    /// Settings of the `difficulty` preset with a known `secret`.
+    pub fn new(difficulty: Difficulty, secret: u32) -> Self {
+        Self {
+            secret,
+            range: difficulty.range(),
+            max_attempts: difficulty.max_attempts(),
+            difficulty,
+        }
+    }
+
+    /// Settings of the `difficulty` preset with a secret drawn at random from its range.
+    pub fn random(difficulty: Difficulty) -> Self {
+        Self::random_with(difficulty, &mut rand::thread_rng())
+    }
+
+    /// Like [`Settings::random`], drawing the secret from `rng`.
+    pub fn random_with<R: Rng + ?Sized>(difficulty: Difficulty, rng: &mut R) -> Self {
+        Self::new(difficulty, rng.gen_range(difficulty.range()))
+    }
+}
@@ -0,0 +1,338 @@
+//! Automatic players and a simulator that measures how well each one does.
+//!
+//! The [`Solver`] only talks to the game through its text interface, the same reader and
+//! writer a person would use, so every simulated game also exercises [`Config::run`].
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, Read, Write},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use clap::ValueEnum;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{Config, Difficulty, Outcome, Settings, HIGHER, LOWER, PROMPT};
+
+/// How the [`Solver`] picks its next guess among the numbers still possible
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Strategy {
+    /// The middle number, halving the possibilities on every guess
+    #[default]
+    Binary,
+    /// Any of the possible numbers, at random
+    Random,
+    /// The smallest possible number, one by one
+    Linear,
+}
+
+impl Strategy {
+    /// Next guess, knowing the secret is between `low` and `high`.
+    pub fn guess<R: Rng + ?Sized>(self, low: u32, high: u32, rng: &mut R) -> u32 {
+        match self {
+            Strategy::Binary => low + (high - low) / 2,
+            Strategy::Random => rng.gen_range(low..=high),
+            Strategy::Linear => low,
+        }
+    }
+}
+
+/// Plays a game by reading its output and answering each prompt with a guess.
+pub struct Solver<R> {
+    strategy: Strategy,
+    rng: R,
+}
+
+impl<R: Rng> Solver<R> {
+    pub fn new(strategy: Strategy, rng: R) -> Self {
+        Self { strategy, rng }
+    }
+
+    /// Plays until `game_output` ends, guessing in `range`.
+    ///
+    /// `game_output` is what the game prints and `game_input` is where the game reads the
+    /// guesses from.
+    pub fn play<I: BufRead, O: Write>(
+        &mut self,
+        range: (u32, u32),
+        mut game_output: I,
+        mut game_input: O,
+    ) -> io::Result<()> {
+        let (mut low, mut high) = range;
+        let mut last = None;
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if game_output.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            match (line.trim_end(), last) {
+                (line, _) if line.starts_with(PROMPT) => {
+                    let guess = self.strategy.guess(low, high, &mut self.rng);
+                    writeln!(game_input, "{}", guess)?;
+                    game_input.flush()?;
+                    last = Some(guess);
+                }
+                (LOWER, Some(guess)) => low = guess + 1,
+                (HIGHER, Some(guess)) => high = guess - 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Plays one game of `settings` with a [`Solver`] and returns how it ended.
+///
+/// The game runs in its own thread, connected to the solver by in-memory pipes.
+pub fn solve<R: Rng>(settings: Settings, strategy: Strategy, rng: R) -> io::Result<Outcome> {
+    let range = (*settings.range.start(), *settings.range.end());
+    let (to_game, game_input) = pipe();
+    let (game_output, from_game) = pipe();
+
+    let game = thread::spawn(move || {
+        Config::new(
+            settings,
+            Some(Box::new(game_input)),
+            Some(Box::new(game_output)),
+        )
+        .run()
+    });
+    Solver::new(strategy, rng).play(range, from_game, to_game)?;
+
+    game.join()
+        .map_err(|_| io::Error::other("the game thread panicked"))?
+}
+
+/// How many attempts a [`Strategy`] needed over many games
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// Number of games won with each number of attempts
+    pub attempts: BTreeMap<u32, usize>,
+    pub lost: usize,
+}
+
+impl Simulation {
+    pub fn games(&self) -> usize {
+        self.attempts.values().sum::<usize>() + self.lost
+    }
+
+    pub fn won(&self) -> usize {
+        self.games() - self.lost
+    }
+
+    /// Mean attempts of the games won
+    pub fn mean_attempts(&self) -> f64 {
+        let total: usize = self
+            .attempts
+            .iter()
+            .map(|(attempts, games)| *attempts as usize * games)
+            .sum();
+        total as f64 / self.won().max(1) as f64
+    }
+
+    /// Prints the distribution of attempts as a histogram.
+    pub fn print<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let games = self.games().max(1);
+        writeln!(
+            output,
+            "{} games, {} won ({:.1}%), {:.2} attempts on average",
+            self.games(),
+            self.won(),
+            self.won() as f64 * 100.0 / games as f64,
+            self.mean_attempts()
+        )?;
+
+        let widest = self
+            .attempts
+            .values()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(self.lost);
+        let bar = |count: usize| "#".repeat((count * 40).div_ceil(widest.max(1)));
+        for (attempts, count) in &self.attempts {
+            writeln!(output, "{:>5} {:>7} {}", attempts, count, bar(*count))?;
+        }
+        if self.lost > 0 {
+            writeln!(output, "{:>5} {:>7} {}", "lost", self.lost, bar(self.lost))?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `games` games of `difficulty` with `strategy`.
+///
+/// Secrets and random guesses come from a generator seeded with `seed`, so the same
+/// arguments always give the same [`Simulation`].
+pub fn simulate(
+    difficulty: Difficulty,
+    strategy: Strategy,
+    games: usize,
+    seed: u64,
+) -> io::Result<Simulation> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut simulation = Simulation::default();
+
+    for _ in 0..games {
+        let settings = Settings::random_with(difficulty, &mut rng);
+        match solve(settings, strategy, &mut rng)? {
+            Outcome::Won { attempts, .. } => *simulation.attempts.entry(attempts).or_default() += 1,
+            Outcome::Lost | Outcome::Quit => simulation.lost += 1,
+        }
+    }
+
+    Ok(simulation)
+}
+
+/// Connects a writer in one thread to a reader in another.
+fn pipe() -> (PipeWriter, PipeReader) {
+    let (sender, receiver) = mpsc::channel();
+    (
+        PipeWriter(sender),
+        PipeReader {
+            receiver,
+            buffer: Vec::new(),
+            position: 0,
+        },
+    )
+}
+
+struct PipeWriter(Sender<Vec<u8>>);
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // An empty message would read as the end of the input
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.0
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads what the [`PipeWriter`] wrote; ends when the writer is dropped.
+struct PipeReader {
+    receiver: Receiver<Vec<u8>>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.fill_buf()?.read(buf)?;
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl BufRead for PipeReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position == self.buffer.len() {
+            // A closed channel leaves the buffer empty, which is the end of the input
+            self.buffer = self.receiver.recv().unwrap_or_default();
+            self.position = 0;
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_guess_inside_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        assert_eq!(50, Strategy::Binary.guess(1, 100, &mut rng));
+        assert_eq!(1, Strategy::Linear.guess(1, 100, &mut rng));
+        for _ in 0..100 {
+            assert!((10..=20).contains(&Strategy::Random.guess(10, 20, &mut rng)));
+        }
+    }
+
+    #[test]
+    fn should_solve_with_binary_search() {
+        let settings = Settings::new(Difficulty::Medium, 1000);
+
+        let outcome = solve(settings, Strategy::Binary, StdRng::seed_from_u64(0)).unwrap();
+
+        assert!(matches!(outcome, Outcome::Won { attempts: 10, .. }));
+    }
+
+    #[test]
+    fn should_always_win_with_binary_search() {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for difficulty in Difficulty::ALL {
+            let range = difficulty.range();
+            // The secret that takes a binary search the most attempts to find
+            let hardest = range
+                .clone()
+                .max_by_key(|&secret| {
+                    let (mut low, mut high) = (*range.start(), *range.end());
+                    let mut attempts = 1;
+                    loop {
+                        let guess = Strategy::Binary.guess(low, high, &mut rng);
+                        if guess == secret {
+                            break attempts;
+                        } else if guess < secret {
+                            low = guess + 1;
+                        } else {
+                            high = guess - 1;
+                        }
+                        attempts += 1;
+                    }
+                })
+                .unwrap();
+
+            let settings = Settings::new(difficulty, hardest);
+            let outcome = solve(settings, Strategy::Binary, StdRng::seed_from_u64(0)).unwrap();
+
+            assert!(
+                matches!(outcome, Outcome::Won { .. }),
+                "{difficulty:?} with {hardest}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_lose_with_linear_search() {
+        let settings = Settings::new(Difficulty::Easy, 11);
+
+        let outcome = solve(settings, Strategy::Linear, StdRng::seed_from_u64(0)).unwrap();
+
+        assert_eq!(Outcome::Lost, outcome);
+    }
+
+    #[test]
+    fn should_print_distribution() {
+        let simulation = Simulation {
+            attempts: BTreeMap::from([(1, 1), (2, 2)]),
+            lost: 1,
+        };
+        let mut output = Vec::new();
+
+        simulation.print(&mut output).unwrap();
+
+        assert_eq!(
+            "4 games, 3 won (75.0%), 1.67 attempts on average\n\
+             \x20   1       1 ####################\n\
+             \x20   2       2 ########################################\n\
+             \x20lost       1 ####################\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+}
@@ -1,12 +1,63 @@
 use std::io::BufReader;
 
+use guessing_game::{Difficulty, Outcome, Settings};
+
 #[test]
 fn test_main() {
-    let secret = 2;
+    let settings = Settings::new(Difficulty::Medium, 2);
     let writer = Box::new(Vec::new());
-    let reader: &[u8] = &['2' as u8; 1];
+    let reader: &[u8] = &[b'2'; 1];
     let buf_reader = Box::new(BufReader::new(reader));
-    let mut config = guessing_game::Config::new(secret, Some(buf_reader), Some(writer));
+    let mut config = guessing_game::Config::new(settings, Some(buf_reader), Some(writer));
+
+    let outcome = config.run().expect("Should parser correctly");
+
+    assert!(matches!(outcome, Outcome::Won { attempts: 1, .. }));
+}
+
+#[test]
+fn should_show_saved_scores() {
+    let dir = std::env::temp_dir().join(format!("guessing_game-scores-{}", std::process::id()));
+    let path = dir.join("scores.json");
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut leaderboard = guessing_game::Leaderboard::load(&path).unwrap();
+    leaderboard.add(guessing_game::Score::new(
+        "ana",
+        8,
+        std::time::Duration::from_millis(41_250),
+        Difficulty::Hard,
+    ));
+    leaderboard.save().unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_guessing_game"))
+        .arg("--scores")
+        .arg("--scores-file")
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Hard\n  1. ana - 8 attempts in 41.2s on "));
+}
+
+#[test]
+fn should_simulate_games_deterministically() {
+    let simulation =
+        guessing_game::simulate(Difficulty::Medium, guessing_game::Strategy::Binary, 300, 42)
+            .unwrap();
+
+    assert_eq!(300, simulation.games());
+    assert_eq!(0, simulation.lost);
+    assert!(simulation.attempts.keys().all(|attempts| *attempts <= 10));
+    assert_eq!(
+        simulation,
+        guessing_game::simulate(Difficulty::Medium, guessing_game::Strategy::Binary, 300, 42)
+            .unwrap()
+    );
 
-    config.run().expect("Should parser correctly")
+    let random =
+        guessing_game::simulate(Difficulty::Hard, guessing_game::Strategy::Random, 300, 42)
+            .unwrap();
+    assert!(random.mean_attempts() > simulation.mean_attempts());
 }
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use minigrep::pattern::{AhoCorasick, Horspool};
+use regex::Regex;
+
+const REPETITIONS: usize = 2000;
+/// Padrões do grupo com um conjunto grande de padrões
+const LARGE_PATTERN_SET: usize = 20_000;
+
+const PATTERNS: [&str; 12] = [
+    "Recesso",
+    "Feriado",
+    "Prova",
+    "Seminário",
+    "Mutação",
+    "Debugging",
+    "Ciclomática",
+    "Integração",
+    "Apresentação TP",
+    "Revisão",
+    "Nivelamento",
+    "Entrega",
+];
+
+/// O cronograma repetido até ter algumas dezenas de milhares de linhas
+fn create_lines() -> Vec<&'static str> {
+    let contents = include_str!("../../cronograma.txt");
+    let lines: Vec<_> = contents.lines().collect();
+
+    lines.repeat(REPETITIONS)
+}
+
+/// Padrões como os de uma lista de termos proibidos: muitos, e quase nenhum no cronograma
+fn create_large_pattern_set() -> Vec<String> {
+    let contents = include_str!("../../cronograma.txt");
+    let words: Vec<_> = contents.split_whitespace().collect();
+
+    (0..LARGE_PATTERN_SET)
+        .map(|index| format!("{} {}", words[index % words.len()], index))
+        .collect()
+}
+
+fn custom_criterion() -> Criterion {
+    Criterion::default().measurement_time(Duration::from_secs(10))
+}
+
+pub fn single_long_literal(c: &mut Criterion) {
+    let lines = create_lines();
+    let query = "Métricas de Avaliação de Plano de Teste";
+    let horspool = Horspool::new(query);
+
+    let mut group = c.benchmark_group("Single Long Literal");
+    group.bench_function("str_contains", |b| {
+        b.iter(|| {
+            black_box(&lines)
+                .iter()
+                .filter(|line| line.contains(query))
+                .count()
+        });
+    });
+
+    group.bench_function("boyer_moore_horspool", |b| {
+        b.iter(|| {
+            black_box(&lines)
+                .iter()
+                .filter(|line| horspool.is_match(line))
+                .count()
+        });
+    });
+
+    group.finish();
+}
+
+pub fn many_patterns(c: &mut Criterion) {
+    let lines = create_lines();
+    let automaton = AhoCorasick::new(PATTERNS);
+    let alternation = Regex::new(&PATTERNS.map(regex::escape).join("|")).unwrap();
+
+    let mut group = c.benchmark_group("Many Patterns");
+    group.bench_function("str_contains_each", |b| {
+        b.iter(|| {
+            black_box(&lines)
+                .iter()
+                .filter(|line| PATTERNS.iter().any(|pattern| line.contains(pattern)))
+                .count()
+        });
+    });
+
+    group.bench_function("aho_corasick", |b| {
+        b.iter(|| {
+            black_box(&lines)
+                .iter()
+                .filter(|line| automaton.is_match(line))
+                .count()
+        });
+    });
+
+    group.bench_function("regex_alternation", |b| {
+        b.iter(|| {
+            black_box(&lines)
+                .iter()
+                .filter(|line| alternation.is_match(line))
+                .count()
+        });
+    });
+
+    group.finish();
+}
+
+pub fn large_pattern_set(c: &mut Criterion) {
+    let lines = create_lines();
+    let patterns = create_large_pattern_set();
+    let automaton = AhoCorasick::new(&patterns);
+
+    let mut group = c.benchmark_group("Large Pattern Set");
+    group.bench_function("aho_corasick_build", |b| {
+        b.iter(|| AhoCorasick::new(black_box(&patterns)));
+    });
+
+    group.bench_function("aho_corasick", |b| {
+        b.iter(|| {
+            black_box(&lines)
+                .iter()
+                .filter(|line| automaton.is_match(line))
+                .count()
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    config = custom_criterion();
+    targets = single_long_literal, many_patterns, large_pattern_set
+);
+
+criterion_main!(benches);
@@ -0,0 +1,395 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use clap::{error::ErrorKind, Parser, ValueEnum};
+
+use crate::error::ConfigError;
+
+#[derive(Parser, Debug)]
+#[command(name = "minigrep", version)]
+#[command(about = "Busca as linhas de um arquivo que casam com um padrão", long_about = None)]
+pub struct Args {
+    #[arg(
+        value_name = "QUERY",
+        required_unless_present_any = ["patterns", "pattern_files"],
+        help = "Texto a ser buscado (ou expressão regular, com --regex); com -e ou -f, é um arquivo"
+    )]
+    pub query: Option<String>,
+
+    #[arg(
+        short = 'e',
+        long = "pattern",
+        value_name = "PATTERN",
+        help = "Padrão a ser buscado; pode ser repetido, e a linha casa com qualquer um"
+    )]
+    pub patterns: Vec<String>,
+
+    #[arg(
+        short = 'f',
+        long = "file",
+        value_name = "FILE",
+        help = "Lê os padrões de FILE, um por linha"
+    )]
+    pub pattern_files: Vec<PathBuf>,
+
+    #[arg(
+        value_name = "FILENAME",
+        help = "Arquivos ou diretórios onde buscar; sem eles, ou com `-`, lê da entrada padrão"
+    )]
+    pub filenames: Vec<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Ignora maiúsculas e minúsculas (padrão quando CASE_INSENSITIVE está definida)"
+    )]
+    pub ignore_case: bool,
+
+    #[arg(
+        short = 's',
+        long,
+        overrides_with = "ignore_case",
+        help = "Diferencia maiúsculas e minúsculas, mesmo com CASE_INSENSITIVE definida"
+    )]
+    pub case_sensitive: bool,
+
+    #[arg(
+        short = 'S',
+        long,
+        help = "Só diferencia maiúsculas e minúsculas se a query tiver alguma letra maiúscula"
+    )]
+    pub smart_case: bool,
+
+    #[arg(short = 'v', long, help = "Seleciona as linhas que NÃO casam")]
+    pub invert_match: bool,
+
+    #[arg(short = 'n', long, help = "Imprime o número de cada linha")]
+    pub line_number: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Imprime apenas a quantidade de linhas encontradas"
+    )]
+    pub count: bool,
+
+    #[arg(
+        short = 'l',
+        long,
+        help = "Imprime apenas o nome dos arquivos com linhas encontradas"
+    )]
+    pub files_with_matches: bool,
+
+    #[arg(short, long = "word-regexp", help = "Só casa palavras inteiras")]
+    pub word: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["regex", "word", "patterns", "pattern_files"],
+        help = "Busca aproximada: casa trechos a até N edições (inserção, remoção ou troca de um caractere) da query"
+    )]
+    pub fuzzy: Option<usize>,
+
+    #[arg(
+        long,
+        requires = "fuzzy",
+        help = "Na busca aproximada, não diferencia letras acentuadas (ã, ç) das sem acento"
+    )]
+    pub ignore_accents: bool,
+
+    #[arg(
+        short = 'A',
+        long,
+        value_name = "NUM",
+        help = "Imprime NUM linhas depois de cada linha encontrada"
+    )]
+    pub after_context: Option<usize>,
+
+    #[arg(
+        short = 'B',
+        long,
+        value_name = "NUM",
+        help = "Imprime NUM linhas antes de cada linha encontrada"
+    )]
+    pub before_context: Option<usize>,
+
+    #[arg(
+        short = 'C',
+        long,
+        value_name = "NUM",
+        help = "Imprime NUM linhas antes e depois de cada linha encontrada"
+    )]
+    pub context: Option<usize>,
+
+    #[arg(
+        short,
+        long,
+        help = "Imprime a posição, em bytes no arquivo, de cada ocorrência"
+    )]
+    pub byte_offset: bool,
+
+    #[arg(
+        long,
+        value_name = "WHEN",
+        value_enum,
+        default_value_t,
+        help = "Quando destacar as ocorrências com cores"
+    )]
+    pub color: ColorChoice,
+
+    #[arg(
+        long,
+        help = "Imprime os resultados em JSON Lines, um objeto por linha"
+    )]
+    pub json: bool,
+
+    #[arg(
+        short = 'z',
+        long,
+        conflicts_with = "in_place",
+        help = "Busca dentro de arquivos comprimidos com gzip, bzip2, xz ou zstd"
+    )]
+    pub search_zip: bool,
+
+    #[arg(
+        short,
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["count", "files_with_matches", "invert_match"],
+        help = "Imprime as linhas com cada ocorrência trocada por TEMPLATE; com --regex, $1 ou ${nome} referenciam os grupos de captura"
+    )]
+    pub replace: Option<String>,
+
+    #[arg(
+        long,
+        requires = "replace",
+        conflicts_with = "json",
+        help = "Reescreve os arquivos com as substituições de --replace"
+    )]
+    pub in_place: bool,
+
+    #[arg(
+        long,
+        requires = "in_place",
+        help = "Com --in-place, só mostra o diff das alterações, sem reescrever os arquivos"
+    )]
+    pub dry_run: bool,
+
+    #[arg(
+        short = 'j',
+        long,
+        value_name = "NUM",
+        help = "Quantidade de threads usadas na busca (padrão: uma por núcleo)"
+    )]
+    pub threads: Option<usize>,
+
+    #[arg(
+        short = 'E',
+        long,
+        help = "Interpreta a query como uma expressão regular"
+    )]
+    pub regex: bool,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Busca apenas nos arquivos que casam com GLOB"
+    )]
+    pub include: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Ignora arquivos e diretórios que casam com GLOB"
+    )]
+    pub exclude: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Busca também arquivos e diretórios ocultos ao percorrer diretórios"
+    )]
+    pub hidden: bool,
+
+    #[arg(
+        long,
+        help = "Não respeita as regras de .gitignore e .ignore ao percorrer diretórios"
+    )]
+    pub no_ignore: bool,
+}
+
+#[derive(ValueEnum, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Só usa cores quando a saída é um terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl Args {
+    pub fn parse_from_args<I, T>(args: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        Self::try_parse_from(args).map_err(|err| {
+            let message = err.render().to_string();
+            match err.kind() {
+                ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => ConfigError::Help(message),
+                ErrorKind::MissingRequiredArgument => ConfigError::MissingArgument(message),
+                ErrorKind::UnknownArgument => ConfigError::UnknownArgument(message),
+                _ => ConfigError::InvalidArgument(message),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn test_minigrep_args() {
+        Args::command().debug_assert()
+    }
+
+    #[test]
+    fn should_parse_combined_flags() {
+        let args = Args::parse_from_args(["minigrep", "-ivc", "teste", "cronograma.txt"]).unwrap();
+
+        assert!(args.ignore_case && args.invert_match && args.count);
+        assert!(!args.line_number && !args.files_with_matches && !args.word);
+        assert_eq!(Some("teste".to_string()), args.query);
+    }
+
+    #[test]
+    fn should_accept_query_after_double_dash() {
+        let args = Args::parse_from_args(["minigrep", "-n", "--", "-v", "cronograma.txt"]).unwrap();
+
+        assert!(args.line_number);
+        assert!(!args.invert_match);
+        assert_eq!(Some("-v".to_string()), args.query);
+    }
+
+    #[test]
+    fn should_parse_patterns() {
+        let args = Args::parse_from_args([
+            "minigrep",
+            "-e",
+            "Prova",
+            "--pattern=Recesso",
+            "-f",
+            "padroes.txt",
+            "cronograma.txt",
+        ])
+        .unwrap();
+
+        assert_eq!(vec!["Prova", "Recesso"], args.patterns);
+        assert_eq!(vec![PathBuf::from("padroes.txt")], args.pattern_files);
+        assert_eq!(Some("cronograma.txt".to_string()), args.query);
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "-n"]),
+            Err(ConfigError::MissingArgument(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_replace() {
+        let args = Args::parse_from_args([
+            "minigrep",
+            "-r",
+            "Feriado",
+            "--in-place",
+            "--dry-run",
+            "Recesso",
+            "cronograma.txt",
+        ])
+        .unwrap();
+
+        assert_eq!(Some("Feriado".to_string()), args.replace);
+        assert!(args.in_place && args.dry_run);
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "--in-place", "Recesso", "cronograma.txt"]),
+            Err(ConfigError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "-r", "x", "-c", "Recesso", "cronograma.txt"]),
+            Err(ConfigError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_fuzzy() {
+        let args = Args::parse_from_args([
+            "minigrep",
+            "--fuzzy",
+            "2",
+            "--ignore-accents",
+            "Apresentação",
+            "cronograma.txt",
+        ])
+        .unwrap();
+
+        assert_eq!(Some(2), args.fuzzy);
+        assert!(args.ignore_accents);
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "--fuzzy", "1", "-E", "a+", "cronograma.txt"]),
+            Err(ConfigError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_context() {
+        let args = Args::parse_from_args(["minigrep", "-C", "2", "-A1", "teste", "cronograma.txt"])
+            .unwrap();
+
+        assert_eq!(Some(2), args.context);
+        assert_eq!(Some(1), args.after_context);
+        assert_eq!(None, args.before_context);
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "-A", "x", "teste", "cronograma.txt"]),
+            Err(ConfigError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn should_parse_color() {
+        let args = Args::parse_from_args(["minigrep", "teste", "cronograma.txt"]).unwrap();
+        assert_eq!(ColorChoice::Auto, args.color);
+
+        let args = Args::parse_from_args([
+            "minigrep",
+            "--color=always",
+            "-b",
+            "teste",
+            "cronograma.txt",
+        ])
+        .unwrap();
+        assert_eq!(ColorChoice::Always, args.color);
+        assert!(args.byte_offset);
+
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "--color=sometimes", "teste", "cronograma.txt"]),
+            Err(ConfigError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn should_return_typed_errors() {
+        assert!(matches!(
+            Args::parse_from_args(["minigrep"]),
+            Err(ConfigError::MissingArgument(_))
+        ));
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "--nope", "teste", "cronograma.txt"]),
+            Err(ConfigError::UnknownArgument(_))
+        ));
+        assert!(matches!(
+            Args::parse_from_args(["minigrep", "--help"]),
+            Err(ConfigError::Help(_))
+        ));
+    }
+}
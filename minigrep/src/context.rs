@@ -0,0 +1,165 @@
+//! Linhas de contexto ao redor das linhas encontradas (`-A`, `-B` e `-C`)
+
+use std::collections::VecDeque;
+
+/// Uma linha de saída, numerada a partir de 1
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Line<L> {
+    Match(usize, L),
+    Context(usize, L),
+    /// O `--` que separa grupos de linhas não contíguos
+    Separator,
+}
+
+/// Janela de contexto que recebe as linhas em ordem, uma por vez.
+///
+/// Guarda no máximo `before` linhas, então funciona sem ter o arquivo inteiro em memória.
+/// Janelas que se sobrepõem ou se encostam são unidas em um único grupo, como no `grep`.
+pub(crate) struct ContextWindow<L> {
+    before: usize,
+    after: usize,
+    buffer: VecDeque<(usize, L)>,
+    after_remaining: usize,
+    last_emitted: Option<usize>,
+}
+
+impl<L> ContextWindow<L> {
+    pub fn new(before: usize, after: usize) -> Self {
+        Self {
+            before,
+            after,
+            // `-B` pode ser enorme; a janela cresce só até o que o arquivo tiver
+            buffer: VecDeque::new(),
+            after_remaining: 0,
+            last_emitted: None,
+        }
+    }
+
+    /// Recebe a próxima linha e adiciona a `out` as linhas que já podem ser impressas.
+    pub fn push(&mut self, number: usize, line: L, is_match: bool, out: &mut Vec<Line<L>>) {
+        if is_match {
+            let first = self.buffer.front().map_or(number, |(first, _)| *first);
+            let has_context = self.before > 0 || self.after > 0;
+            if has_context && self.last_emitted.is_some_and(|last| first > last + 1) {
+                out.push(Line::Separator);
+            }
+            out.extend(
+                self.buffer
+                    .drain(..)
+                    .map(|(number, line)| Line::Context(number, line)),
+            );
+            out.push(Line::Match(number, line));
+            self.last_emitted = Some(number);
+            self.after_remaining = self.after;
+        } else if self.after_remaining > 0 {
+            out.push(Line::Context(number, line));
+            self.last_emitted = Some(number);
+            self.after_remaining -= 1;
+        } else if self.before > 0 {
+            if self.buffer.len() == self.before {
+                self.buffer.pop_front();
+            }
+            self.buffer.push_back((number, line));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(
+        lines: &[(&'static str, bool)],
+        before: usize,
+        after: usize,
+    ) -> Vec<Line<&'static str>> {
+        let mut window = ContextWindow::new(before, after);
+        let mut out = Vec::new();
+        for (index, (line, is_match)) in lines.iter().enumerate() {
+            window.push(index + 1, *line, *is_match, &mut out);
+        }
+        out
+    }
+
+    const LINES: [(&str, bool); 8] = [
+        ("1 02/02 Apresentação da Disciplina", false),
+        ("2 03/02 Revisão e Nivelamento", false),
+        ("3 09/02 Revisão e Nivelamento", false),
+        ("4 10/02 Introdução a Teste de Software", true),
+        ("5 16/02 Teste Unitário", true),
+        ("6 17/02 Testes no Processo", false),
+        ("7 23/02 Teste Funcional", false),
+        ("8 24/02 Teste Funcional", true),
+    ];
+
+    #[test]
+    fn should_print_only_matches_without_context() {
+        assert_eq!(
+            vec![
+                Line::Match(4, LINES[3].0),
+                Line::Match(5, LINES[4].0),
+                Line::Match(8, LINES[7].0),
+            ],
+            window(&LINES, 0, 0)
+        );
+    }
+
+    #[test]
+    fn should_separate_groups() {
+        assert_eq!(
+            vec![
+                Line::Context(3, LINES[2].0),
+                Line::Match(4, LINES[3].0),
+                Line::Match(5, LINES[4].0),
+                Line::Separator,
+                Line::Context(7, LINES[6].0),
+                Line::Match(8, LINES[7].0),
+            ],
+            window(&LINES, 1, 0)
+        );
+    }
+
+    #[test]
+    fn should_merge_overlapping_windows() {
+        assert_eq!(
+            vec![
+                Line::Context(2, LINES[1].0),
+                Line::Context(3, LINES[2].0),
+                Line::Match(4, LINES[3].0),
+                Line::Match(5, LINES[4].0),
+                Line::Context(6, LINES[5].0),
+                Line::Context(7, LINES[6].0),
+                Line::Match(8, LINES[7].0),
+            ],
+            window(&LINES, 2, 1)
+        );
+    }
+
+    #[test]
+    fn should_accept_huge_context() {
+        let mut expected: Vec<_> = LINES[..7]
+            .iter()
+            .enumerate()
+            .map(|(index, (line, _))| Line::Context(index + 1, *line))
+            .collect();
+        expected[3] = Line::Match(4, LINES[3].0);
+        expected[4] = Line::Match(5, LINES[4].0);
+        expected.push(Line::Match(8, LINES[7].0));
+
+        assert_eq!(expected, window(&LINES, usize::MAX, usize::MAX));
+    }
+
+    #[test]
+    fn should_merge_adjacent_windows() {
+        assert_eq!(
+            vec![
+                Line::Match(4, LINES[3].0),
+                Line::Match(5, LINES[4].0),
+                Line::Context(6, LINES[5].0),
+                Line::Context(7, LINES[6].0),
+                Line::Match(8, LINES[7].0),
+            ],
+            window(&LINES, 0, 2)
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Descompressão transparente de arquivos `.gz`, `.bz2`, `.xz` e `.zst` (`-z`)
+//!
+//! O formato é detectado pelos bytes mágicos do início do arquivo, não pela extensão, então
+//! logs rotacionados como `syslog.1.gz` ou a entrada padrão também funcionam.
+
+use std::io::{self, BufRead, BufReader, Read};
+
+use bzip2::read::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use xz2::bufread::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+/// Formatos de compressão reconhecidos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+/// Bytes lidos do início do arquivo para detectar o formato: os do maior cabeçalho, o do
+/// bzip2 seguido do início do primeiro bloco
+const MAGIC_LEN: usize = 10;
+/// Início de um bloco de bzip2 (os dígitos de pi em BCD)
+const BZIP2_BLOCK: &[u8] = &[0x31, 0x41, 0x59, 0x26, 0x53, 0x59];
+/// Fim de um stream de bzip2 (os dígitos da raiz de pi), que segue o cabeçalho quando o
+/// arquivo está vazio
+const BZIP2_END: &[u8] = &[0x17, 0x72, 0x45, 0x38, 0x50, 0x90];
+
+impl Format {
+    /// Detecta o formato pelos primeiros bytes do arquivo.
+    ///
+    /// O `BZh` do bzip2 também inicia textos comuns, então só conta seguido do tamanho dos
+    /// blocos e do início de um bloco.
+    pub fn detect(header: &[u8]) -> Option<Format> {
+        match header {
+            [0x1f, 0x8b, ..] => Some(Format::Gzip),
+            [b'B', b'Z', b'h', b'1'..=b'9', block @ ..]
+                if block.starts_with(BZIP2_BLOCK) || block.starts_with(BZIP2_END) =>
+            {
+                Some(Format::Bzip2)
+            }
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Some(Format::Xz),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Format::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Envolve `reader` com o descompressor do formato detectado no seu início, ou o retorna
+/// como está se ele não estiver comprimido.
+///
+/// Arquivos com vários membros concatenados, como os gerados por `cat a.gz b.gz`, são lidos
+/// até o fim.
+pub(crate) fn decompress<'a, R: BufRead + 'a>(mut reader: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let (format, reader): (_, Box<dyn BufRead + 'a>) = if reader.fill_buf()?.len() >= MAGIC_LEN {
+        (Format::detect(reader.fill_buf()?), Box::new(reader))
+    } else {
+        // Uma leitura pode trazer menos bytes que o cabeçalho, como as de um pipe; os bytes
+        // lidos aqui voltam para a frente do resto
+        let mut header = Vec::with_capacity(MAGIC_LEN);
+        (&mut reader)
+            .take(MAGIC_LEN as u64)
+            .read_to_end(&mut header)?;
+        let format = Format::detect(&header);
+        (format, Box::new(io::Cursor::new(header).chain(reader)))
+    };
+    let Some(format) = format else {
+        return Ok(reader);
+    };
+
+    Ok(match format {
+        Format::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Format::Bzip2 => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+        Format::Xz => Box::new(BufReader::new(XzDecoder::new_multi_decoder(reader))),
+        Format::Zstd => Box::new(BufReader::new(ZstdDecoder::with_buffer(reader)?)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    const CONTENTS: &str = "02/03 Recesso\n14/04 Recesso\n";
+
+    fn compress(format: Format) -> Vec<u8> {
+        let data = CONTENTS.as_bytes();
+        match format {
+            Format::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Format::Bzip2 => {
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Format::Xz => {
+                let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(data).unwrap();
+                encoder.finish().unwrap()
+            }
+            Format::Zstd => zstd::encode_all(data, 0).unwrap(),
+        }
+    }
+
+    fn read(compressed: &[u8]) -> String {
+        let mut contents = String::new();
+        decompress(compressed)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    #[test]
+    fn should_decompress_every_format() {
+        for format in [Format::Gzip, Format::Bzip2, Format::Xz, Format::Zstd] {
+            let compressed = compress(format);
+
+            assert_eq!(Some(format), Format::detect(&compressed));
+            assert_eq!(CONTENTS, read(&compressed), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn should_read_concatenated_members() {
+        let compressed = [compress(Format::Gzip), compress(Format::Gzip)].concat();
+
+        assert_eq!(CONTENTS.repeat(2), read(&compressed));
+    }
+
+    #[test]
+    fn should_pass_plain_text_through() {
+        assert_eq!(None, Format::detect(CONTENTS.as_bytes()));
+        assert_eq!(CONTENTS, read(CONTENTS.as_bytes()));
+        assert_eq!(None, Format::detect(b"BZh plain text"));
+        assert_eq!(None, Format::detect(b"BZh9 plain text"));
+        assert_eq!("BZh plain text\n", read(b"BZh plain text\n"));
+        assert_eq!("BZh", read(b"BZh"));
+    }
+
+    #[test]
+    fn should_detect_format_from_short_reads() {
+        for format in [Format::Gzip, Format::Bzip2, Format::Xz, Format::Zstd] {
+            let compressed = compress(format);
+            let mut contents = String::new();
+
+            decompress(BufReader::with_capacity(1, compressed.as_slice()))
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+
+            assert_eq!(CONTENTS, contents, "{format:?}");
+        }
+    }
+}
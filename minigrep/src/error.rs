@@ -0,0 +1,26 @@
+use std::{error::Error, fmt};
+
+/// Erros possíveis ao montar um [`Config`](crate::Config) a partir da linha de comando.
+///
+/// Cada variante carrega a mensagem já formatada, com o uso do programa.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// `--help` ou `--version` foram pedidos; não é uma falha de fato
+    Help(String),
+    MissingArgument(String),
+    UnknownArgument(String),
+    InvalidArgument(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Help(message)
+            | ConfigError::MissingArgument(message)
+            | ConfigError::UnknownArgument(message)
+            | ConfigError::InvalidArgument(message) => write!(f, "{}", message.trim_end()),
+        }
+    }
+}
+
+impl Error for ConfigError {}
@@ -0,0 +1,323 @@
+//! Comparação sem diferenciar maiúsculas e minúsculas usando o _case folding_ completo do
+//! Unicode (`CaseFolding.txt`, status C e F), sem alocar memória por linha.
+//!
+//! O _case folding_ difere do `to_lowercase` em alguns caracteres: `ß` e `ẞ` viram `ss`,
+//! ligaduras como `ﬁ` são desfeitas e o sigma final `ς` vira `σ`. A única adaptação ao
+//! turco é o `İ` (I com ponto), que vira `i` em vez de `i` seguido do ponto combinante,
+//! para que `İzmir` seja encontrado por `izmir`.
+
+use std::ops::Range;
+
+/// Caracteres cujo _case folding_ é diferente do `to_lowercase`, em ordem crescente.
+///
+/// Os alfabetos Cherokee, que fazem o _folding_ para maiúsculas, são tratados em [`fold`].
+const FOLDING: &[(char, &str)] = &[
+    ('\u{b5}', "\u{3bc}"),
+    ('\u{df}', "ss"),
+    ('\u{149}', "\u{2bc}n"),
+    ('\u{17f}', "s"),
+    ('\u{1f0}', "j\u{30c}"),
+    ('\u{345}', "\u{3b9}"),
+    ('\u{390}', "\u{3b9}\u{308}\u{301}"),
+    ('\u{3b0}', "\u{3c5}\u{308}\u{301}"),
+    ('\u{3c2}', "\u{3c3}"),
+    ('\u{3d0}', "\u{3b2}"),
+    ('\u{3d1}', "\u{3b8}"),
+    ('\u{3d5}', "\u{3c6}"),
+    ('\u{3d6}', "\u{3c0}"),
+    ('\u{3f0}', "\u{3ba}"),
+    ('\u{3f1}', "\u{3c1}"),
+    ('\u{3f5}', "\u{3b5}"),
+    ('\u{587}', "\u{565}\u{582}"),
+    ('\u{1c80}', "\u{432}"),
+    ('\u{1c81}', "\u{434}"),
+    ('\u{1c82}', "\u{43e}"),
+    ('\u{1c83}', "\u{441}"),
+    ('\u{1c84}', "\u{442}"),
+    ('\u{1c85}', "\u{442}"),
+    ('\u{1c86}', "\u{44a}"),
+    ('\u{1c87}', "\u{463}"),
+    ('\u{1c88}', "\u{a64b}"),
+    ('\u{1e96}', "h\u{331}"),
+    ('\u{1e97}', "t\u{308}"),
+    ('\u{1e98}', "w\u{30a}"),
+    ('\u{1e99}', "y\u{30a}"),
+    ('\u{1e9a}', "a\u{2be}"),
+    ('\u{1e9b}', "\u{1e61}"),
+    ('\u{1e9e}', "ss"),
+    ('\u{1f50}', "\u{3c5}\u{313}"),
+    ('\u{1f52}', "\u{3c5}\u{313}\u{300}"),
+    ('\u{1f54}', "\u{3c5}\u{313}\u{301}"),
+    ('\u{1f56}', "\u{3c5}\u{313}\u{342}"),
+    ('\u{1f80}', "\u{1f00}\u{3b9}"),
+    ('\u{1f81}', "\u{1f01}\u{3b9}"),
+    ('\u{1f82}', "\u{1f02}\u{3b9}"),
+    ('\u{1f83}', "\u{1f03}\u{3b9}"),
+    ('\u{1f84}', "\u{1f04}\u{3b9}"),
+    ('\u{1f85}', "\u{1f05}\u{3b9}"),
+    ('\u{1f86}', "\u{1f06}\u{3b9}"),
+    ('\u{1f87}', "\u{1f07}\u{3b9}"),
+    ('\u{1f88}', "\u{1f00}\u{3b9}"),
+    ('\u{1f89}', "\u{1f01}\u{3b9}"),
+    ('\u{1f8a}', "\u{1f02}\u{3b9}"),
+    ('\u{1f8b}', "\u{1f03}\u{3b9}"),
+    ('\u{1f8c}', "\u{1f04}\u{3b9}"),
+    ('\u{1f8d}', "\u{1f05}\u{3b9}"),
+    ('\u{1f8e}', "\u{1f06}\u{3b9}"),
+    ('\u{1f8f}', "\u{1f07}\u{3b9}"),
+    ('\u{1f90}', "\u{1f20}\u{3b9}"),
+    ('\u{1f91}', "\u{1f21}\u{3b9}"),
+    ('\u{1f92}', "\u{1f22}\u{3b9}"),
+    ('\u{1f93}', "\u{1f23}\u{3b9}"),
+    ('\u{1f94}', "\u{1f24}\u{3b9}"),
+    ('\u{1f95}', "\u{1f25}\u{3b9}"),
+    ('\u{1f96}', "\u{1f26}\u{3b9}"),
+    ('\u{1f97}', "\u{1f27}\u{3b9}"),
+    ('\u{1f98}', "\u{1f20}\u{3b9}"),
+    ('\u{1f99}', "\u{1f21}\u{3b9}"),
+    ('\u{1f9a}', "\u{1f22}\u{3b9}"),
+    ('\u{1f9b}', "\u{1f23}\u{3b9}"),
+    ('\u{1f9c}', "\u{1f24}\u{3b9}"),
+    ('\u{1f9d}', "\u{1f25}\u{3b9}"),
+    ('\u{1f9e}', "\u{1f26}\u{3b9}"),
+    ('\u{1f9f}', "\u{1f27}\u{3b9}"),
+    ('\u{1fa0}', "\u{1f60}\u{3b9}"),
+    ('\u{1fa1}', "\u{1f61}\u{3b9}"),
+    ('\u{1fa2}', "\u{1f62}\u{3b9}"),
+    ('\u{1fa3}', "\u{1f63}\u{3b9}"),
+    ('\u{1fa4}', "\u{1f64}\u{3b9}"),
+    ('\u{1fa5}', "\u{1f65}\u{3b9}"),
+    ('\u{1fa6}', "\u{1f66}\u{3b9}"),
+    ('\u{1fa7}', "\u{1f67}\u{3b9}"),
+    ('\u{1fa8}', "\u{1f60}\u{3b9}"),
+    ('\u{1fa9}', "\u{1f61}\u{3b9}"),
+    ('\u{1faa}', "\u{1f62}\u{3b9}"),
+    ('\u{1fab}', "\u{1f63}\u{3b9}"),
+    ('\u{1fac}', "\u{1f64}\u{3b9}"),
+    ('\u{1fad}', "\u{1f65}\u{3b9}"),
+    ('\u{1fae}', "\u{1f66}\u{3b9}"),
+    ('\u{1faf}', "\u{1f67}\u{3b9}"),
+    ('\u{1fb2}', "\u{1f70}\u{3b9}"),
+    ('\u{1fb3}', "\u{3b1}\u{3b9}"),
+    ('\u{1fb4}', "\u{3ac}\u{3b9}"),
+    ('\u{1fb6}', "\u{3b1}\u{342}"),
+    ('\u{1fb7}', "\u{3b1}\u{342}\u{3b9}"),
+    ('\u{1fbc}', "\u{3b1}\u{3b9}"),
+    ('\u{1fbe}', "\u{3b9}"),
+    ('\u{1fc2}', "\u{1f74}\u{3b9}"),
+    ('\u{1fc3}', "\u{3b7}\u{3b9}"),
+    ('\u{1fc4}', "\u{3ae}\u{3b9}"),
+    ('\u{1fc6}', "\u{3b7}\u{342}"),
+    ('\u{1fc7}', "\u{3b7}\u{342}\u{3b9}"),
+    ('\u{1fcc}', "\u{3b7}\u{3b9}"),
+    ('\u{1fd2}', "\u{3b9}\u{308}\u{300}"),
+    ('\u{1fd3}', "\u{3b9}\u{308}\u{301}"),
+    ('\u{1fd6}', "\u{3b9}\u{342}"),
+    ('\u{1fd7}', "\u{3b9}\u{308}\u{342}"),
+    ('\u{1fe2}', "\u{3c5}\u{308}\u{300}"),
+    ('\u{1fe3}', "\u{3c5}\u{308}\u{301}"),
+    ('\u{1fe4}', "\u{3c1}\u{313}"),
+    ('\u{1fe6}', "\u{3c5}\u{342}"),
+    ('\u{1fe7}', "\u{3c5}\u{308}\u{342}"),
+    ('\u{1ff2}', "\u{1f7c}\u{3b9}"),
+    ('\u{1ff3}', "\u{3c9}\u{3b9}"),
+    ('\u{1ff4}', "\u{3ce}\u{3b9}"),
+    ('\u{1ff6}', "\u{3c9}\u{342}"),
+    ('\u{1ff7}', "\u{3c9}\u{342}\u{3b9}"),
+    ('\u{1ffc}', "\u{3c9}\u{3b9}"),
+    ('\u{fb00}', "ff"),
+    ('\u{fb01}', "fi"),
+    ('\u{fb02}', "fl"),
+    ('\u{fb03}', "ffi"),
+    ('\u{fb04}', "ffl"),
+    ('\u{fb05}', "st"),
+    ('\u{fb06}', "st"),
+    ('\u{fb13}', "\u{574}\u{576}"),
+    ('\u{fb14}', "\u{574}\u{565}"),
+    ('\u{fb15}', "\u{574}\u{56b}"),
+    ('\u{fb16}', "\u{57e}\u{576}"),
+    ('\u{fb17}', "\u{574}\u{56d}"),
+];
+
+/// O resultado do _folding_ de um caractere: no máximo três caracteres.
+#[derive(Debug, Clone)]
+pub(crate) struct Folded {
+    chars: [char; 3],
+    len: usize,
+    next: usize,
+}
+
+impl Folded {
+    fn one(c: char) -> Self {
+        Self {
+            chars: [c, '\0', '\0'],
+            len: 1,
+            next: 0,
+        }
+    }
+
+    fn from_chars(chars: impl Iterator<Item = char>) -> Self {
+        let mut folded = Self {
+            chars: ['\0'; 3],
+            len: 0,
+            next: 0,
+        };
+        for c in chars.take(3) {
+            folded.chars[folded.len] = c;
+            folded.len += 1;
+        }
+        folded
+    }
+}
+
+impl Iterator for Folded {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars[..self.len].get(self.next).copied();
+        self.next += 1;
+        c
+    }
+}
+
+/// Aplica o _case folding_ a um caractere.
+pub(crate) fn fold(c: char) -> Folded {
+    if c.is_ascii() {
+        return Folded::one(c.to_ascii_lowercase());
+    }
+
+    match c as u32 {
+        0x130 => return Folded::one('i'),
+        0x13A0..=0x13F5 => return Folded::one(c),
+        0x13F8..=0x13FD | 0xAB70..=0xABBF => {
+            let upper = if (c as u32) < 0xAB70 {
+                c as u32 - 8
+            } else {
+                c as u32 - 0xAB70 + 0x13A0
+            };
+            return Folded::one(char::from_u32(upper).unwrap_or(c));
+        }
+        _ => {}
+    }
+
+    match FOLDING.binary_search_by_key(&c, |(from, _)| *from) {
+        Ok(index) => Folded::from_chars(FOLDING[index].1.chars()),
+        Err(_) => Folded::from_chars(c.to_lowercase()),
+    }
+}
+
+/// Uma query já com _case folding_, pronta para ser buscada em várias linhas.
+#[derive(Debug)]
+pub(crate) struct FoldedQuery {
+    query: Vec<char>,
+}
+
+impl FoldedQuery {
+    pub fn new(query: &str) -> Self {
+        Self {
+            query: query.chars().flat_map(fold).collect(),
+        }
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        self.find_at(line, 0).is_some()
+    }
+
+    /// Intervalos, em bytes da linha original, de todas as ocorrências não sobrepostas.
+    pub fn find_iter(&self, line: &str) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        if self.query.is_empty() {
+            return spans;
+        }
+
+        let mut start = 0;
+        while let Some(span) = self.find_at(line, start) {
+            start = span.end;
+            spans.push(span);
+        }
+        spans
+    }
+
+    /// Primeira ocorrência a partir do byte `start`, que precisa estar em uma fronteira de
+    /// caractere. Uma ocorrência sempre começa e termina em caracteres inteiros da linha:
+    /// `s` não casa com metade do `ß`.
+    fn find_at(&self, line: &str, start: usize) -> Option<Range<usize>> {
+        if self.query.is_empty() {
+            return Some(start..start);
+        }
+
+        line[start..].char_indices().find_map(|(index, _)| {
+            let begin = start + index;
+            self.match_len(&line[begin..]).map(|len| begin..begin + len)
+        })
+    }
+
+    /// Se `haystack` começa com a query, retorna o tamanho em bytes do trecho que casou.
+    fn match_len(&self, haystack: &str) -> Option<usize> {
+        let mut expected = self.query.iter();
+        for (index, c) in haystack.char_indices() {
+            for folded in fold(c) {
+                if expected.next() != Some(&folded) {
+                    return None;
+                }
+            }
+            if expected.len() == 0 {
+                return Some(index + c.len_utf8());
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_folding_table_sorted() {
+        assert!(FOLDING.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn should_fold_special_cases() {
+        assert_eq!("ss", fold('ß').collect::<String>());
+        assert_eq!("ss", fold('ẞ').collect::<String>());
+        assert_eq!("fi", fold('ﬁ').collect::<String>());
+        assert_eq!("σ", fold('ς').collect::<String>());
+        assert_eq!("i", fold('İ').collect::<String>());
+        assert_eq!("ç", fold('Ç').collect::<String>());
+        assert_eq!("Ꭰ", fold('ꭰ').collect::<String>());
+    }
+
+    #[test]
+    fn should_match_german_sharp_s() {
+        let query = FoldedQuery::new("STRASSE");
+
+        assert!(query.is_match("Fechado na Hauptstraße"));
+        assert!(query.is_match("HAUPTSTRAẞE"));
+        assert_eq!(vec![9..16], query.find_iter("Fechado: Straße"));
+    }
+
+    #[test]
+    fn should_not_match_half_of_an_expansion() {
+        let query = FoldedQuery::new("s");
+
+        assert!(!query.is_match("ß"));
+        assert_eq!(vec![0..1, 3..4], query.find_iter("sßs"));
+    }
+
+    #[test]
+    fn should_match_turkish_dotted_i() {
+        let query = FoldedQuery::new("izmir");
+
+        assert!(query.is_match("Viagem a İZMİR"));
+        assert_eq!(vec![0..5], FoldedQuery::new("İzmir").find_iter("izmir"));
+    }
+
+    #[test]
+    fn should_match_accented_text() {
+        let query = FoldedQuery::new("apresentação");
+
+        assert_eq!(vec![9..23], query.find_iter("12 16/03 APRESENTAÇÃO TP 1"));
+    }
+}
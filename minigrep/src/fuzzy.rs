@@ -0,0 +1,177 @@
+//! Busca aproximada (`--fuzzy N`): ocorrências a até `N` edições da query
+//!
+//! A distância é a de Levenshtein, contada em caracteres: cada inserção, remoção ou troca
+//! de um caractere custa uma edição. A busca usa o algoritmo de Sellers, a programação
+//! dinâmica da distância de edição em que a ocorrência pode começar em qualquer posição da
+//! linha.
+
+use std::ops::Range;
+
+use crate::accent::strip_accent;
+
+/// Query buscada de forma aproximada
+#[derive(Debug)]
+pub(crate) struct FuzzyQuery {
+    query: Vec<char>,
+    max_distance: usize,
+    case_sensitive: bool,
+    /// Compara as letras sem os acentos (`--ignore-accents`)
+    ignore_accents: bool,
+}
+
+impl FuzzyQuery {
+    pub fn new(
+        query: &str,
+        max_distance: usize,
+        case_sensitive: bool,
+        ignore_accents: bool,
+    ) -> Self {
+        let mut fuzzy = Self {
+            query: Vec::new(),
+            max_distance,
+            case_sensitive,
+            ignore_accents,
+        };
+        fuzzy.query = query.chars().filter_map(|c| fuzzy.normalize(c)).collect();
+        fuzzy
+    }
+
+    /// Caractere usado na comparação, ou `None` se ele deve ser desconsiderado.
+    fn normalize(&self, c: char) -> Option<char> {
+        let c = if self.ignore_accents {
+            strip_accent(c)?
+        } else {
+            c
+        };
+        if self.case_sensitive {
+            Some(c)
+        } else {
+            c.to_lowercase().next()
+        }
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        self.query.len() <= self.max_distance || self.find_at(&self.chars(line), 0).is_some()
+    }
+
+    /// Intervalos, em bytes, das ocorrências aproximadas, sem sobreposição. Quando a query
+    /// tem até `N` caracteres, qualquer linha casa, mas não há o que destacar.
+    pub fn find_iter(&self, line: &str) -> Vec<Range<usize>> {
+        let chars = self.chars(line);
+        let offset = |index: usize| chars.get(index).map_or(line.len(), |(offset, _)| *offset);
+
+        let mut spans = Vec::new();
+        let mut start = 0;
+        while let Some(found) = self.find_at(&chars, start) {
+            spans.push(offset(found.start)..offset(found.end));
+            start = found.end;
+        }
+        spans
+    }
+
+    /// Os caracteres normalizados de `line` com a posição, em bytes, de cada um.
+    fn chars(&self, line: &str) -> Vec<(usize, char)> {
+        line.char_indices()
+            .filter_map(|(offset, c)| Some((offset, self.normalize(c)?)))
+            .collect()
+    }
+
+    /// Primeira ocorrência não vazia a partir de `from`, em índices de `text`.
+    ///
+    /// Cada coluna guarda, para cada prefixo da query, a menor distância de uma ocorrência
+    /// que termina no caractere atual e onde ela começa. Ao achar a primeira ocorrência
+    /// dentro do limite, a busca continua enquanto a distância diminuir, para preferir
+    /// `Prova` a `Prov` quando a query é `Prova`.
+    fn find_at(&self, text: &[(usize, char)], from: usize) -> Option<Range<usize>> {
+        if self.query.len() <= self.max_distance {
+            return None;
+        }
+
+        // (distância, início) de cada prefixo da query
+        let mut column: Vec<(usize, usize)> = (0..=self.query.len()).map(|i| (i, from)).collect();
+        let mut best: Option<(usize, Range<usize>)> = None;
+
+        for (index, &(_, c)) in text.iter().enumerate().skip(from) {
+            let mut previous = column[0];
+            column[0] = (0, index + 1);
+            for (i, &q) in self.query.iter().enumerate() {
+                let substitution = (previous.0 + usize::from(q != c), previous.1);
+                let deletion = (column[i + 1].0 + 1, column[i + 1].1);
+                let insertion = (column[i].0 + 1, column[i].1);
+                previous = column[i + 1];
+                column[i + 1] =
+                    [deletion, insertion]
+                        .into_iter()
+                        .fold(
+                            substitution,
+                            |best, other| if other.0 < best.0 { other } else { best },
+                        );
+            }
+
+            let (distance, start) = column[self.query.len()];
+            match &best {
+                Some((best_distance, _)) if distance >= *best_distance => break,
+                _ if distance <= self.max_distance && start <= index => {
+                    best = Some((distance, start..index + 1));
+                }
+                Some(_) => break,
+                None => {}
+            }
+        }
+
+        best.map(|(_, range)| range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(query: &FuzzyQuery, line: &str) -> Vec<(usize, usize)> {
+        query
+            .find_iter(line)
+            .into_iter()
+            .map(|span| (span.start, span.end))
+            .collect()
+    }
+
+    #[test]
+    fn should_match_within_distance() {
+        let query = FuzzyQuery::new("Apresentação", 1, true, false);
+
+        assert!(query.is_match("12 16/03 Apresentaçao TP 1"));
+        assert!(query.is_match("12 16/03 Apresentação TP 1"));
+        assert!(query.is_match("12 16/03 Apresntação TP 1"));
+        assert!(!query.is_match("12 16/03 Apresntaçao TP 1"));
+        assert_eq!(vec![(9, 22)], spans(&query, "12 16/03 Apresentaçao TP 1"));
+    }
+
+    #[test]
+    fn should_prefer_closer_occurrence() {
+        let query = FuzzyQuery::new("Prova", 1, true, false);
+
+        assert_eq!(vec![(9, 14)], spans(&query, "15 24/03 Provas 1"));
+        assert_eq!(vec![(0, 4), (7, 12)], spans(&query, "Prva e Prova"));
+    }
+
+    #[test]
+    fn should_ignore_case_and_accents() {
+        let query = FuzzyQuery::new("apresentacao", 0, false, true);
+
+        assert!(query.is_match("12 16/03 APRESENTAÇÃO TP 1"));
+        assert!(query.is_match("12 16/03 Apresentac\u{327}a\u{303}o TP 1"));
+        assert_eq!(
+            vec![(9, 25)],
+            spans(&query, "12 16/03 Apresentac\u{327}a\u{303}o TP 1")
+        );
+        assert!(!FuzzyQuery::new("apresentacao", 1, false, false).is_match("Apresentação"));
+    }
+
+    #[test]
+    fn should_match_everything_with_short_query() {
+        let query = FuzzyQuery::new("ab", 2, true, false);
+
+        assert!(query.is_match("02/03 Recesso"));
+        assert!(query.find_iter("02/03 Recesso").is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+//! Regras de arquivos ignorados no formato do `.gitignore`
+//!
+//! Cada diretório pode ter um `.gitignore` e um `.ignore`, com as regras do git: `#` inicia
+//! um comentário, `!` reinclui o que uma regra anterior ignorou, uma `/` no final só casa
+//! diretórios e uma `/` no início ou no meio prende a regra ao diretório do arquivo. Sem
+//! `/`, a regra casa em qualquer profundidade.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobMatcher};
+
+/// Arquivos de regras lidos em cada diretório, do mais para o menos prioritário
+pub const IGNORE_FILES: [&str; 2] = [".ignore", ".gitignore"];
+
+/// Resultado de testar um caminho contra as regras
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Match {
+    /// Nenhuma regra casou
+    None,
+    Ignore,
+    /// Casou com uma regra `!`, que reinclui o caminho
+    Whitelist,
+}
+
+#[derive(Debug)]
+struct Rule {
+    glob: GlobMatcher,
+    negated: bool,
+    dir_only: bool,
+}
+
+/// As regras de um arquivo `.gitignore`, relativas ao diretório onde ele está
+#[derive(Debug)]
+pub struct Gitignore {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl Gitignore {
+    /// Interpreta `contents` como um `.gitignore` do diretório `root`. Assim como no git,
+    /// linhas com padrões inválidos são ignoradas.
+    pub fn parse(root: &Path, contents: &str) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            rules: contents.lines().filter_map(parse_rule).collect(),
+        }
+    }
+
+    /// Lê o arquivo `path`, se ele existir, com as regras relativas ao seu diretório.
+    pub fn from_file(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        let root = path.parent().unwrap_or(Path::new(""));
+        Some(Self::parse(root, &contents))
+    }
+
+    /// Testa `path`, que deve estar dentro do diretório das regras. Quando várias regras
+    /// casam, vale a última.
+    pub fn matched(&self, path: &Path, is_dir: bool) -> Match {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return Match::None;
+        };
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.glob.is_match(relative))
+            .map_or(Match::None, |rule| {
+                if rule.negated {
+                    Match::Whitelist
+                } else {
+                    Match::Ignore
+                }
+            })
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Rule> {
+    // Espaços no final são ignorados, a menos que escapados com `\`
+    let mut pattern = line.trim_end();
+    if pattern.ends_with('\\') && line.len() > pattern.len() {
+        pattern = &line[..pattern.len() + 1];
+    }
+    if pattern.is_empty() || pattern.starts_with('#') {
+        return None;
+    }
+
+    let negated = pattern.starts_with('!');
+    if negated {
+        pattern = &pattern[1..];
+    }
+    let dir_only = pattern.ends_with('/');
+    pattern = pattern.trim_end_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    let glob = if anchored {
+        pattern.to_string()
+    } else {
+        format!("**/{pattern}")
+    };
+
+    let glob = GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .backslash_escape(true)
+        .build()
+        .ok()?
+        .compile_matcher();
+
+    Some(Rule {
+        glob,
+        negated,
+        dir_only,
+    })
+}
+
+/// As regras dos diretórios abertos durante uma travessia em profundidade.
+///
+/// Regras de diretórios mais profundos têm prioridade sobre as dos diretórios acima deles,
+/// e as do `.ignore` sobre as do `.gitignore` do mesmo diretório. Os caminhos testados
+/// devem ser absolutos.
+#[derive(Debug, Default)]
+pub struct IgnoreStack {
+    /// Regras de cada diretório, do mais raso para o mais profundo
+    levels: Vec<Vec<Gitignore>>,
+    /// Quantidade de níveis acima da raiz da travessia, que nunca são removidos
+    base: usize,
+}
+
+impl IgnoreStack {
+    /// Começa uma travessia em `root`, que deve ser absoluto, com as regras dos diretórios
+    /// acima dele até a raiz do repositório git. Fora de um repositório, ou quando `root` já
+    /// é a raiz de um (mesmo dentro de outro), só valem as regras de dentro de `root`, que
+    /// entram com [`IgnoreStack::push`].
+    pub fn new(root: &Path) -> Self {
+        let mut levels = Vec::new();
+        let is_repository = |dir: &Path| dir.join(".git").exists();
+        let repository = if is_repository(root) {
+            None
+        } else {
+            root.ancestors().skip(1).position(is_repository)
+        };
+        if let Some(position) = repository {
+            let ancestors: Vec<_> = root.ancestors().skip(1).take(position + 1).collect();
+            levels.extend(ancestors.into_iter().rev().map(load));
+        }
+
+        Self {
+            base: levels.len(),
+            levels,
+        }
+    }
+
+    /// Adiciona as regras do diretório `dir`, onde a travessia acabou de entrar.
+    pub fn push(&mut self, dir: &Path) {
+        self.levels.push(load(dir));
+    }
+
+    /// Mantém só as regras dos `depth` diretórios abertos a partir da raiz, descartando as
+    /// dos diretórios que já foram percorridos.
+    pub fn truncate(&mut self, depth: usize) {
+        self.levels.truncate(self.base + depth);
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.levels
+            .iter()
+            .rev()
+            .flatten()
+            .map(|rules| rules.matched(path, is_dir))
+            .find(|matched| *matched != Match::None)
+            == Some(Match::Ignore)
+    }
+}
+
+fn load(dir: &Path) -> Vec<Gitignore> {
+    IGNORE_FILES
+        .iter()
+        .filter_map(|name| Gitignore::from_file(&dir.join(name)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn matched(contents: &str, path: &str, is_dir: bool) -> Match {
+        Gitignore::parse(Path::new("/repo"), contents)
+            .matched(&Path::new("/repo").join(path), is_dir)
+    }
+
+    #[test]
+    fn should_match_at_any_depth() {
+        assert_eq!(Match::Ignore, matched("*.rlib\n", "a.rlib", false));
+        assert_eq!(
+            Match::Ignore,
+            matched("*.rlib\n", "target/debug/a.rlib", false)
+        );
+        assert_eq!(Match::None, matched("*.rlib\n", "src/lib.rs", false));
+        assert_eq!(Match::None, matched("*.rlib\n", "/outro/a.rlib", false));
+    }
+
+    #[test]
+    fn should_anchor_patterns_with_slash() {
+        assert_eq!(Match::Ignore, matched("/Cargo.lock\n", "Cargo.lock", false));
+        assert_eq!(
+            Match::None,
+            matched("/Cargo.lock\n", "minigrep/Cargo.lock", false)
+        );
+        assert_eq!(
+            Match::Ignore,
+            matched("doc/*.txt\n", "doc/notas.txt", false)
+        );
+        assert_eq!(
+            Match::None,
+            matched("doc/*.txt\n", "doc/sub/notas.txt", false)
+        );
+        assert_eq!(
+            Match::Ignore,
+            matched("doc/**/*.txt\n", "doc/sub/notas.txt", false)
+        );
+    }
+
+    #[test]
+    fn should_match_only_directories_with_trailing_slash() {
+        assert_eq!(Match::Ignore, matched("target/\n", "target", true));
+        assert_eq!(Match::Ignore, matched("target/\n", "minigrep/target", true));
+        assert_eq!(Match::None, matched("target/\n", "target", false));
+    }
+
+    #[test]
+    fn should_use_last_matching_rule() {
+        let contents = "# logs\n*.log\n!importante.log\n\n";
+
+        assert_eq!(Match::Ignore, matched(contents, "debug.log", false));
+        assert_eq!(Match::Whitelist, matched(contents, "importante.log", false));
+        assert_eq!(Match::Ignore, matched("!a.log\n*.log\n", "a.log", false));
+    }
+
+    #[test]
+    fn should_escape_special_characters() {
+        assert_eq!(Match::Ignore, matched("\\#notas\n", "#notas", false));
+        assert_eq!(Match::Ignore, matched("\\!urgente\n", "!urgente", false));
+        assert_eq!(Match::Ignore, matched("espaço\\ \n", "espaço ", false));
+        assert_eq!(Match::Ignore, matched("espaço  \n", "espaço", false));
+    }
+
+    #[test]
+    fn should_prefer_deeper_rules() {
+        let root = env::temp_dir().join("minigrep_ignore_stack");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join(".gitignore"), "*.txt\n").unwrap();
+        fs::write(root.join(".ignore"), "!leia.txt\n").unwrap();
+        fs::write(root.join("docs/.gitignore"), "!*.txt\n").unwrap();
+
+        let mut stack = IgnoreStack::new(&root);
+        stack.push(&root);
+        assert!(stack.is_ignored(&root.join("notas.txt"), false));
+        assert!(!stack.is_ignored(&root.join("leia.txt"), false));
+        assert!(stack.is_ignored(&root.join("docs/notas.txt"), false));
+
+        stack.push(&root.join("docs"));
+        assert!(!stack.is_ignored(&root.join("docs/notas.txt"), false));
+
+        stack.truncate(1);
+        assert!(stack.is_ignored(&root.join("outros.txt"), false));
+    }
+
+    #[test]
+    fn should_stop_at_nested_repository() {
+        let outer = env::temp_dir().join("minigrep_ignore_nested");
+        let _ = fs::remove_dir_all(&outer);
+        let inner = outer.join("vendor/lib");
+        fs::create_dir_all(outer.join(".git")).unwrap();
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(outer.join(".gitignore"), "*.txt\n").unwrap();
+
+        let mut stack = IgnoreStack::new(&inner);
+        stack.push(&inner);
+        assert!(stack.is_ignored(&inner.join("notas.txt"), false));
+
+        fs::create_dir(inner.join(".git")).unwrap();
+        let mut stack = IgnoreStack::new(&inner);
+        stack.push(&inner);
+        assert!(!stack.is_ignored(&inner.join("notas.txt"), false));
+    }
+}
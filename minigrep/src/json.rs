@@ -0,0 +1,212 @@
+//! Saída em [JSON Lines](https://jsonlines.org/): um objeto JSON por linha
+//!
+//! Cada objeto tem um `type` (`begin`, `match`, `context`, `end` ou `summary`) e os
+//! dados em `data`, para que editores e scripts consumam os resultados sem interpretar
+//! a saída em texto.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::sink::{Output, SearchLine, Sink, Stats};
+
+#[derive(Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+enum Record<'a> {
+    Begin {
+        path: Cow<'a, str>,
+    },
+    Match(LineRecord<'a>),
+    Context(LineRecord<'a>),
+    End {
+        path: Cow<'a, str>,
+        stats: &'a Stats,
+    },
+    Summary {
+        stats: &'a Stats,
+        elapsed_secs: f64,
+    },
+}
+
+#[derive(Serialize)]
+struct LineRecord<'a> {
+    path: Cow<'a, str>,
+    line_number: usize,
+    /// Posição, em bytes, do início da linha no arquivo
+    absolute_offset: usize,
+    text: &'a str,
+    submatches: Vec<Submatch<'a>>,
+}
+
+/// Uma ocorrência da query, com posições em bytes relativas ao início da linha
+#[derive(Serialize)]
+struct Submatch<'a> {
+    #[serde(rename = "match")]
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> LineRecord<'a> {
+    fn new(path: &'a Path, line: &'a SearchLine) -> Self {
+        Self {
+            path: path.to_string_lossy(),
+            line_number: line.number,
+            absolute_offset: line.offset,
+            text: &line.text,
+            submatches: line
+                .spans
+                .iter()
+                .map(|span| Submatch {
+                    text: &line.text[span.clone()],
+                    start: line.byte_position(span.start),
+                    end: line.byte_position(span.end),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub(crate) struct JsonPrinter<W> {
+    out: W,
+}
+
+impl<W: Write> JsonPrinter<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    fn record(&mut self, record: &Record) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        writeln!(self.out)
+    }
+}
+
+impl<W: Write> Sink for JsonPrinter<W> {
+    fn begin(&mut self, path: &Path) -> io::Result<()> {
+        self.record(&Record::Begin {
+            path: path.to_string_lossy(),
+        })
+    }
+
+    fn matched(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        self.record(&Record::Match(LineRecord::new(path, line)))?;
+        Ok(true)
+    }
+
+    fn context(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        self.record(&Record::Context(LineRecord::new(path, line)))?;
+        Ok(true)
+    }
+
+    // Com `-c` e `-l`, o registro `end` já informa a quantidade de linhas encontradas
+
+    fn end(&mut self, path: &Path, stats: &Stats) -> io::Result<()> {
+        self.record(&Record::End {
+            path: path.to_string_lossy(),
+            stats,
+        })
+    }
+
+    fn summary(&mut self, stats: &Stats, elapsed: Duration) -> io::Result<()> {
+        self.record(&Record::Summary {
+            stats,
+            elapsed_secs: elapsed.as_secs_f64(),
+        })
+    }
+}
+
+impl<W: Write> Output for JsonPrinter<W> {
+    fn separator(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_raw(&mut self, output: &[u8]) -> io::Result<()> {
+        self.out.write_all(output)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn records(out: Vec<u8>) -> Vec<Value> {
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn should_print_one_record_per_line() {
+        let path = Path::new("cronograma.txt");
+        let line = SearchLine {
+            number: 17,
+            offset: 509,
+            text: "15 24/03 Prova 1 e Prova 2".to_string(),
+            spans: vec![9..14, 19..24],
+            invalid: Vec::new(),
+        };
+        let stats = Stats {
+            searches: 1,
+            searches_with_match: 1,
+            matched_lines: 1,
+            matches: 2,
+        };
+        let mut printer = JsonPrinter::new(Vec::new());
+
+        printer.begin(path).unwrap();
+        printer.matched(path, &line).unwrap();
+        printer.context_break().unwrap();
+        printer.end(path, &stats).unwrap();
+
+        assert_eq!(
+            vec![
+                json!({"type": "begin", "data": {"path": "cronograma.txt"}}),
+                json!({"type": "match", "data": {
+                    "path": "cronograma.txt",
+                    "line_number": 17,
+                    "absolute_offset": 509,
+                    "text": "15 24/03 Prova 1 e Prova 2",
+                    "submatches": [
+                        {"match": "Prova", "start": 9, "end": 14},
+                        {"match": "Prova", "start": 19, "end": 24}
+                    ]
+                }}),
+                json!({"type": "end", "data": {
+                    "path": "cronograma.txt",
+                    "stats": {
+                        "searches": 1,
+                        "searches_with_match": 1,
+                        "matched_lines": 1,
+                        "matches": 2
+                    }
+                }}),
+            ],
+            records(printer.out)
+        );
+    }
+
+    #[test]
+    fn should_print_summary() {
+        let mut printer = JsonPrinter::new(Vec::new());
+        printer
+            .summary(&Stats::default(), Duration::from_millis(1500))
+            .unwrap();
+
+        let records = records(printer.out);
+        assert_eq!("summary", records[0]["type"]);
+        assert_eq!(1.5, records[0]["data"]["elapsed_secs"]);
+        assert_eq!(0, records[0]["data"]["stats"]["matched_lines"]);
+    }
+}
@@ -4,94 +4,420 @@
 
 //! Essa biblioteca simula a funcionalidade básica do `grep program`
 
+mod accent;
+mod arg;
+mod context;
+mod decompress;
+mod error;
+mod fold;
+mod fuzzy;
+pub mod ignore;
+mod json;
+mod matcher;
+mod parallel;
+pub mod pattern;
+mod printer;
+mod replace;
+mod searcher;
+mod sink;
+mod walk;
+
 use std::env;
 use std::error::Error;
-use std::fs;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use arg::Args;
+pub use arg::ColorChoice;
+pub use error::ConfigError;
+pub use matcher::{Matcher, PatternMatcher};
+pub use searcher::Searcher;
+pub use sink::{SearchLine, Sink, Stats};
+
+/// Nome de arquivo que representa a entrada padrão
+pub const STDIN_FILENAME: &str = "-";
+
+/// Nome impresso para a entrada padrão quando a saída tem prefixo de arquivo
+const STDIN_NAME: &str = "(standard input)";
 
+#[derive(Debug, Default)]
 pub struct Config {
     pub query: String,
-    pub filename: String,
+    /// Outros padrões buscados junto com a `query` (`-e` e `-f`); uma linha casa se casar
+    /// com qualquer um deles
+    pub patterns: Vec<String>,
+    /// Arquivos ou diretórios onde buscar; [`STDIN_FILENAME`] lê da entrada padrão
+    pub filenames: Vec<String>,
     pub case_sensitive: bool,
+    /// Ignora `case_sensitive` e só diferencia maiúsculas e minúsculas quando a query tem
+    /// alguma letra maiúscula (`-S`)
+    pub smart_case: bool,
+    /// Interpreta a `query` como uma expressão regular (`-E`, `--regex`)
+    pub regex: bool,
+    /// Globs de arquivos a buscar dentro dos diretórios de `filenames` (`--include=GLOB`)
+    pub include: Vec<String>,
+    /// Globs de arquivos e diretórios a ignorar (`--exclude=GLOB`)
+    pub exclude: Vec<String>,
+    /// Busca também arquivos e diretórios ocultos ao percorrer diretórios (`--hidden`)
+    pub hidden: bool,
+    /// Não respeita as regras de `.gitignore` e `.ignore` ao percorrer diretórios
+    /// (`--no-ignore`)
+    pub no_ignore: bool,
+    /// Seleciona as linhas que não casam (`-v`)
+    pub invert_match: bool,
+    /// Prefixa cada linha com o seu número (`-n`)
+    pub line_number: bool,
+    /// Imprime apenas a quantidade de linhas encontradas (`-c`)
+    pub count: bool,
+    /// Imprime apenas o nome dos arquivos com alguma linha encontrada (`-l`)
+    pub files_with_matches: bool,
+    /// Só casa palavras inteiras (`-w`)
+    pub word: bool,
+    /// Busca aproximada: casa trechos a até esta distância de Levenshtein da `query`
+    /// (`--fuzzy N`)
+    pub fuzzy: Option<usize>,
+    /// Na busca aproximada, compara as letras sem os acentos (`--ignore-accents`)
+    pub ignore_accents: bool,
+    /// Quantidade de linhas impressas antes de cada linha encontrada (`-B`, `-C`)
+    pub before_context: usize,
+    /// Quantidade de linhas impressas depois de cada linha encontrada (`-A`, `-C`)
+    pub after_context: usize,
+    /// Prefixa cada linha com a posição, em bytes no arquivo, de cada ocorrência (`-b`)
+    pub byte_offset: bool,
+    /// Quando destacar as ocorrências com cores ANSI (`--color`)
+    pub color: ColorChoice,
+    /// Quantidade de threads usadas para buscar em vários arquivos (`-j`); `0` usa uma por
+    /// núcleo disponível
+    pub threads: usize,
+    /// Imprime os resultados em JSON Lines (`--json`)
+    pub json: bool,
+    /// Descomprime arquivos `.gz`, `.bz2`, `.xz` e `.zst`, detectados pelo conteúdo, antes
+    /// da busca (`-z`)
+    pub search_zip: bool,
+    /// Substitui cada ocorrência por este modelo, que, com `regex`, pode referenciar os
+    /// grupos de captura com `$1` ou `${nome}` (`--replace`)
+    pub replace: Option<String>,
+    /// Reescreve os arquivos com as substituições em vez de imprimir as linhas (`--in-place`)
+    pub in_place: bool,
+    /// Com `in_place`, só imprime o diff das alterações, sem reescrever (`--dry-run`)
+    pub dry_run: bool,
 }
 
 impl Config {
-    pub fn new(mut args: std::env::Args) -> Result<Config, &'static str> {
-        args.next();
+    /// Monta o [`Config`] a partir dos argumentos da linha de comando, incluindo o nome do programa.
+    ///
+    /// Sem `-i`, `-s` ou `-S`, a busca ignora maiúsculas e minúsculas quando a variável de
+    /// ambiente `CASE_INSENSITIVE` está definida. `-i` e `-s` têm prioridade sobre `-S`.
+    ///
+    /// Com `-e` ou `-f`, os padrões vêm dessas opções e o primeiro argumento posicional
+    /// também é um arquivo onde buscar. Cada linha de um arquivo de `-f` é um padrão.
+    pub fn new<I, T>(args: I) -> Result<Config, ConfigError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let args = Args::parse_from_args(args)?;
 
-        let query = match args.next() {
-            Some(arg) => arg,
-            None => return Err("Não recebi a query string"),
+        let case_sensitive = if args.ignore_case {
+            false
+        } else if args.case_sensitive {
+            true
+        } else {
+            env::var("CASE_INSENSITIVE").is_err()
         };
 
-        let filename = match args.next() {
-            Some(arg) => arg,
-            None => return Err("Não recebi um nome de arquivo"),
-        };
-
-        let case_sensitive = env::var("CASE_INSENSITIVE").is_err();
+        let (query, patterns, filenames) =
+            if args.patterns.is_empty() && args.pattern_files.is_empty() {
+                (args.query.unwrap_or_default(), Vec::new(), args.filenames)
+            } else {
+                let mut patterns = args.patterns;
+                for path in &args.pattern_files {
+                    patterns.extend(read_patterns(path)?);
+                }
+                if patterns.is_empty() {
+                    return Err(ConfigError::MissingArgument(
+                        "nenhum padrão para buscar nos arquivos de -f".to_string(),
+                    ));
+                }
+                let query = patterns.remove(0);
+                let filenames = args.query.into_iter().chain(args.filenames).collect();
+                (query, patterns, filenames)
+            };
 
         Ok(Config {
             query,
-            filename,
+            patterns,
+            filenames: if filenames.is_empty() {
+                vec![STDIN_FILENAME.to_string()]
+            } else {
+                filenames
+            },
             case_sensitive,
+            smart_case: args.smart_case && !args.ignore_case && !args.case_sensitive,
+            regex: args.regex,
+            include: args.include,
+            exclude: args.exclude,
+            hidden: args.hidden,
+            no_ignore: args.no_ignore,
+            invert_match: args.invert_match,
+            line_number: args.line_number,
+            count: args.count,
+            files_with_matches: args.files_with_matches,
+            word: args.word,
+            fuzzy: args.fuzzy,
+            ignore_accents: args.ignore_accents,
+            before_context: args.before_context.or(args.context).unwrap_or_default(),
+            after_context: args.after_context.or(args.context).unwrap_or_default(),
+            byte_offset: args.byte_offset,
+            color: args.color,
+            threads: args.threads.unwrap_or_default(),
+            json: args.json,
+            search_zip: args.search_zip,
+            replace: args.replace,
+            in_place: args.in_place,
+            dry_run: args.dry_run,
         })
     }
+
+    /// A `query` seguida dos outros `patterns`
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        iter::once(self.query.as_str()).chain(self.patterns.iter().map(String::as_str))
+    }
+}
+
+/// Lê um arquivo de padrões de `-f`, um por linha.
+fn read_patterns(path: &Path) -> Result<Vec<String>, ConfigError> {
+    fs::read_to_string(path)
+        .map(|contents| contents.lines().map(str::to_string).collect())
+        .map_err(|err| {
+            ConfigError::InvalidArgument(format!(
+                "não foi possível ler o arquivo de padrões {}: {}",
+                path.display(),
+                err
+            ))
+        })
 }
 
 /// ### Descrição
 /// Essa função executa a busca de acordo com o argumento [`Config`] passado
 ///
+/// Os diretórios em `filenames` são percorridos recursivamente, ignorando arquivos
+/// binários, ocultos e os listados em `.gitignore` ou `.ignore`. Quando a busca envolve
+/// mais de um arquivo, cada linha encontrada é impressa com o prefixo `caminho:`. Os
+/// arquivos são buscados em paralelo, mas a saída de cada um é impressa inteira e na ordem
+/// em que foram informados (ou encontrados). Com um único arquivo, com `-j 1` ou com a
+/// entrada padrão, cada linha é impressa assim que encontrada.
+///
+/// Com `-A`, `-B` ou `-C`, as linhas de contexto são impressas com `-` no lugar de `:`
+/// e cada grupo de linhas não contíguas é separado por `--`.
+///
+/// Com `--color=auto` (o padrão), as ocorrências só são destacadas quando a saída é um
+/// terminal. Com `-b`, cada linha é prefixada pela posição, em bytes, de suas ocorrências.
+///
+/// Com `--replace`, as linhas encontradas são impressas com as ocorrências substituídas e,
+/// com `--in-place`, os arquivos são reescritos (veja [`Config::in_place`]).
+///
+/// Com `--json`, cada arquivo é impresso como um registro `begin`, um registro `match` ou
+/// `context` por linha e um registro `end` com as estatísticas do arquivo, e a busca termina
+/// com um registro `summary`.
+///
+/// O arquivo é lido linha a linha, então a memória usada não depende do seu tamanho.
+/// O nome de arquivo `-` faz a busca na entrada padrão.
+///
 /// Retorna um [`Result<(), Box<dyn Error>>`]
-
+///
 /// ### Exemplo
 /// ```
-/// fn main() {
-///     let config = minigrep::Config{
-///            case_sensitive: true,
-///            filename: "cronograma.txt".to_string(),
-///            query: "feriado".to_string()
-///        };
-
-///     let result = minigrep::run(config).is_ok();
-
-///     assert!(result);
-
-/// }
+/// let config = minigrep::Config {
+///     case_sensitive: true,
+///     filenames: vec!["../cronograma.txt".to_string()],
+///     query: "feriado".to_string(),
+///     ..Default::default()
+/// };
+///
+/// let result = minigrep::run(config).is_ok();
+///
+/// assert!(result);
 /// ```
-
 pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let contents = fs::read_to_string(config.filename)?;
+    let searcher = Searcher::from_config(&config)?;
+    let inputs = inputs(&config)?;
+    let show_path = config.filenames.len() > 1
+        || config
+            .filenames
+            .iter()
+            .any(|filename| Path::new(filename).is_dir());
+    let threads = match config.threads {
+        0 => parallel::default_threads(),
+        threads => threads,
+    };
 
-    let results = if config.case_sensitive {
-        search(&config.query, &contents)
-    } else {
-        search_case_insensitive(&config.query, &contents)
+    if config.in_place {
+        return replace::rewrite_all(&config, searcher.matcher(), &inputs, threads);
+    }
+
+    let started = Instant::now();
+    let stdout = io::stdout();
+    let color = match config.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => stdout.is_terminal() && !config.json,
     };
+    let mut output = sink::new_output(BufWriter::new(stdout.lock()), &config, color, show_path);
+
+    let separate = (config.before_context > 0 || config.after_context > 0)
+        && !config.count
+        && !config.files_with_matches;
+    let mut stats = Stats::default();
 
-    for line in results {
-        println!("{}", line);
+    if threads == 1 || inputs.len() <= 1 || inputs.iter().any(|input| input.stdin) {
+        // Sem paralelismo, cada linha vai direto para o stdout; com a entrada padrão ou no
+        // terminal, assim que é encontrada.
+        let line_buffered = stdout.is_terminal() || inputs.iter().any(|input| input.stdin);
+        let mut streaming = sink::Streaming::new(output.as_mut(), separate, line_buffered);
+        for input in &inputs {
+            if let Some(file_stats) = search_input(&searcher, input, &mut streaming)? {
+                stats += file_stats;
+            }
+        }
+    } else {
+        // Cada arquivo é buscado em uma thread que envia a saída em pedaços; só a thread
+        // principal escreve no stdout, na ordem dos arquivos.
+        let mut printed = false;
+        parallel::for_each_streamed(
+            &inputs,
+            threads,
+            |input, writer| {
+                let mut file_output = sink::new_output(writer, &config, color, show_path);
+                search_input(&searcher, input, file_output.as_mut())
+            },
+            |streamed| match streamed {
+                parallel::Streamed::Output { chunk, first } => {
+                    if first && printed && separate {
+                        output.separator()?;
+                    }
+                    printed = true;
+                    output.write_raw(&chunk)
+                }
+                parallel::Streamed::Done(result) => {
+                    if let Some(file_stats) = result? {
+                        stats += file_stats;
+                    }
+                    Ok(())
+                }
+            },
+        )?;
     }
+    output.summary(&stats, started.elapsed())?;
+    output.flush()?;
 
     Ok(())
 }
 
-fn search<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
-    contents
-        .lines()
-        .filter(|line| line.contains(query))
-        .collect()
+/// Um arquivo (ou a entrada padrão) a ser buscado
+#[derive(Debug)]
+struct Input {
+    /// Nome impresso nos prefixos e com `-l`
+    path: PathBuf,
+    stdin: bool,
+    /// Arquivos encontrados ao percorrer diretórios são ignorados se forem binários
+    walked: bool,
+}
+
+/// Expande os diretórios de `config.filenames` na lista de arquivos a buscar.
+fn inputs(config: &Config) -> Result<Vec<Input>, Box<dyn Error>> {
+    let filter = walk::FileFilter::new(&config.include, &config.exclude)?;
+    let options = walk::WalkOptions {
+        hidden: config.hidden,
+        no_ignore: config.no_ignore,
+    };
+    let mut inputs = Vec::new();
+
+    for filename in &config.filenames {
+        let path = Path::new(filename);
+        if filename == STDIN_FILENAME {
+            inputs.push(Input {
+                path: PathBuf::from(STDIN_NAME),
+                stdin: true,
+                walked: false,
+            });
+        } else if path.is_dir() {
+            inputs.extend(
+                walk::files(path, &filter, options)?
+                    .into_iter()
+                    .map(|path| Input {
+                        path,
+                        stdin: false,
+                        walked: true,
+                    }),
+            );
+        } else {
+            inputs.push(Input {
+                path: path.to_path_buf(),
+                stdin: false,
+                walked: false,
+            });
+        }
+    }
+
+    Ok(inputs)
 }
 
-fn search_case_insensitive<'a>(query: &str, contents: &'a str) -> Vec<&'a str> {
-    contents
-        .lines()
-        .filter(|line| line.to_lowercase().contains(&query.to_lowercase()))
-        .collect()
+/// Busca em um [`Input`], entregando os resultados a `sink`. Retorna `None` se o arquivo
+/// foi ignorado por ser binário.
+///
+/// Com `-z`, arquivos comprimidos são descomprimidos antes da busca, e a detecção de
+/// binários olha o conteúdo já descomprimido.
+fn search_input<M: Matcher, S: Sink + ?Sized>(
+    searcher: &Searcher<M>,
+    input: &Input,
+    sink: &mut S,
+) -> io::Result<Option<Stats>> {
+    let reader: Box<dyn BufRead> = if input.stdin {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(&input.path)?))
+    };
+    let mut reader = searcher.decompress(reader)?;
+
+    if input.walked && walk::is_binary(reader.fill_buf()?) {
+        return Ok(None);
+    }
+    searcher.search_reader(&input.path, reader, sink).map(Some)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::printer::Printer;
+
+    fn config(query: &str, case_sensitive: bool) -> Config {
+        Config {
+            query: query.to_string(),
+            case_sensitive,
+            ..Default::default()
+        }
+    }
+
+    fn search(config: &Config, contents: &str) -> Vec<SearchLine> {
+        let mut lines = Vec::new();
+        Searcher::from_config(config)
+            .unwrap()
+            .search_reader(Path::new(STDIN_NAME), contents.as_bytes(), &mut lines)
+            .unwrap();
+        lines
+    }
+
+    fn lines(config: &Config, contents: &str) -> Vec<String> {
+        search(config, contents)
+            .into_iter()
+            .map(|line| line.text)
+            .collect()
+    }
 
     #[test]
     fn should_match_case_sensitive() {
@@ -105,7 +431,10 @@ mod tests {
 14 23/03 Revisão Prova 1
 15 24/03 Prova 1"#;
 
-        assert_eq!(vec!["11 10/03 teste Estrutural"], search(query, contents));
+        assert_eq!(
+            vec!["11 10/03 teste Estrutural"],
+            lines(&config(query, true), contents)
+        );
     }
 
     #[test]
@@ -126,7 +455,149 @@ mod tests {
                 "10 09/03 Teste Estrutural",
                 "11 10/03 teste Estrutural"
             ],
-            search_case_insensitive(query, contents)
+            lines(&config(query, false), contents)
+        );
+    }
+
+    #[test]
+    fn should_match_regex() {
+        let query = r"^\d+ \d{2}/03 (Teste|Prova)";
+        let contents = r#"
+9 03/03 Teste Funcional
+10 09/03 Teste Estrutural
+12 16/03 Apresentação TP 1
+02/03 Recesso
+15 24/03 Prova 1
+16 30/03 Métricas de Avaliação de Plano de Teste"#;
+
+        assert_eq!(
+            vec![
+                "9 03/03 Teste Funcional",
+                "10 09/03 Teste Estrutural",
+                "15 24/03 Prova 1"
+            ],
+            lines(
+                &Config {
+                    regex: true,
+                    ..config(query, true)
+                },
+                contents
+            )
+        );
+    }
+
+    #[test]
+    fn should_match_regex_case_insensitive() {
+        let query = "^\\d+ .*(recesso|feriado)";
+        let contents = r#"
+02/03 Recesso
+21 20/04 Teste Mutação
+21/04 Feriado
+29 25/05 Feriado"#;
+
+        assert_eq!(
+            vec!["29 25/05 Feriado"],
+            lines(
+                &Config {
+                    regex: true,
+                    ..config(query, false)
+                },
+                contents
+            )
+        );
+    }
+
+    #[test]
+    fn should_fail_invalid_regex() {
+        let config = Config {
+            regex: true,
+            ..config("(teste", true)
+        };
+
+        assert!(Searcher::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn should_invert_match_with_line_numbers() {
+        let contents = "\
+9 03/03 Teste Funcional
+02/03 Recesso
+10 09/03 Teste Estrutural";
+
+        let search = |invert_match| {
+            let config = Config {
+                invert_match,
+                ..config("Teste", true)
+            };
+            search(&config, contents)
+                .into_iter()
+                .map(|line| {
+                    let spans: Vec<_> = line.spans.iter().map(|s| (s.start, s.end)).collect();
+                    (line.number, line.offset, line.text, spans)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            vec![(2, 24, "02/03 Recesso".to_string(), vec![])],
+            search(true)
+        );
+        assert_eq!(
+            vec![
+                (1, 0, "9 03/03 Teste Funcional".to_string(), vec![(8, 13)]),
+                (
+                    3,
+                    38,
+                    "10 09/03 Teste Estrutural".to_string(),
+                    vec![(9, 14)]
+                )
+            ],
+            search(false)
+        );
+    }
+
+    #[test]
+    fn should_match_whole_word() {
+        let contents = "\
+12 16/03 Apresentação TP 1
+14 23/03 Revisão Prova 1
+15 24/03 Prova 1
+16 30/03 Provas 2";
+
+        let config = Config {
+            word: true,
+            ..config("prova", false)
+        };
+
+        assert_eq!(
+            vec!["14 23/03 Revisão Prova 1", "15 24/03 Prova 1"],
+            lines(&config, contents)
+        );
+    }
+
+    #[test]
+    fn should_print_results_from_reader() {
+        let contents = "\
+9 03/03 Teste Funcional
+02/03 Recesso
+10 09/03 Teste Estrutural";
+        let config = Config {
+            line_number: true,
+            after_context: 1,
+            ..config("Recesso", true)
+        };
+        let searcher = Searcher::from_config(&config).unwrap();
+        let mut out = Vec::new();
+        let mut printer = Printer::new(&mut out, &config, false, false);
+
+        let stats = searcher
+            .search_reader(Path::new(STDIN_NAME), contents.as_bytes(), &mut printer)
+            .unwrap();
+
+        assert_eq!(1, stats.matched_lines);
+        assert_eq!(
+            "2:02/03 Recesso\n3-10 09/03 Teste Estrutural\n",
+            String::from_utf8(out).unwrap()
         );
     }
 
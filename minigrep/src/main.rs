@@ -1,16 +1,29 @@
 use std::env;
+use std::io;
 
 use std::process;
 
-use minigrep::Config;
+use minigrep::{Config, ConfigError};
 
 fn main() {
     let config = Config::new(env::args()).unwrap_or_else(|err| {
+        if let ConfigError::Help(help) = err {
+            println!("{}", help);
+            process::exit(0);
+        }
+
         eprintln!("Problem parsing arguments: {}", err);
         process::exit(1);
     });
 
     if let Err(e) = minigrep::run(config) {
+        // Quem lê a saída (ex.: `head`) pode fechar o pipe antes do fim da busca
+        if e.downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+        {
+            process::exit(0);
+        }
+
         eprintln!("Application error: {}", e);
 
         process::exit(1);
@@ -0,0 +1,376 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+use crate::fold::FoldedQuery;
+use crate::fuzzy::FuzzyQuery;
+use crate::pattern::{AhoCorasick, Horspool};
+use crate::Config;
+
+/// Tamanho mínimo, em bytes, da query literal buscada com Boyer-Moore-Horspool; abaixo disso
+/// os saltos são pequenos demais para compensar
+const HORSPOOL_MIN_LEN: usize = 8;
+
+/// Decide se uma linha casa com uma busca e onde estão as ocorrências.
+///
+/// É o que o [`Searcher`](crate::Searcher) usa para testar cada linha; implemente para
+/// buscar com outro critério. [`PatternMatcher`] é a implementação usada pelo minigrep.
+pub trait Matcher {
+    fn is_match(&self, line: &str) -> bool;
+
+    /// Intervalos, em bytes, de todas as ocorrências não sobrepostas em `line`.
+    fn find_iter(&self, line: &str) -> Vec<Range<usize>>;
+
+    /// Substitui as ocorrências em `line` por `template`, retornando a nova linha e os
+    /// intervalos, em bytes dentro dela, de cada substituição. Por padrão, `template` é
+    /// literal.
+    fn replace(&self, line: &str, template: &str) -> (String, Vec<Range<usize>>) {
+        replace_literal(self, line, template)
+    }
+}
+
+/// Troca cada ocorrência encontrada por `matcher` pelo `template` literal.
+fn replace_literal<M: Matcher + ?Sized>(
+    matcher: &M,
+    line: &str,
+    template: &str,
+) -> (String, Vec<Range<usize>>) {
+    let mut replaced = String::with_capacity(line.len());
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for span in matcher.find_iter(line) {
+        replaced.push_str(&line[last..span.start]);
+        let start = replaced.len();
+        replaced.push_str(template);
+        spans.push(start..replaced.len());
+        last = span.end;
+    }
+
+    replaced.push_str(&line[last..]);
+    (replaced, spans)
+}
+
+/// O [`Matcher`] dos padrões de um [`Config`], com o algoritmo escolhido pelas opções
+#[derive(Debug)]
+pub struct PatternMatcher {
+    kind: Kind,
+    /// Troca as referências aos grupos de captura nas substituições (`--regex`)
+    expand: bool,
+}
+
+#[derive(Debug)]
+enum Kind {
+    Literal(String),
+    /// Query literal longa, buscada com Boyer-Moore-Horspool
+    Horspool(Horspool),
+    /// Busca literal que ignora maiúsculas e minúsculas com o _case folding_ do Unicode
+    CaseFold(FoldedQuery),
+    /// Vários padrões literais, buscados de uma vez com Aho-Corasick
+    Multi(AhoCorasick),
+    /// Busca aproximada, pela distância de edição
+    Fuzzy(FuzzyQuery),
+    Regex(Regex),
+}
+
+impl PatternMatcher {
+    /// Buscas literais não usam expressão regular. Com `--regex` ou `--word-regexp`, a query
+    /// é escapada (se não for `--regex`) e, com `--word-regexp`, cercada pelas fronteiras
+    /// de palavra `\b`. Assim as posições das ocorrências sempre se referem à linha original.
+    ///
+    /// Com vários padrões (`-e` e `-f`), uma linha casa se casar com qualquer um deles.
+    /// Padrões literais são buscados com Aho-Corasick; para ignorar maiúsculas e minúsculas,
+    /// eles viram uma alternação na expressão regular.
+    ///
+    /// Com `--fuzzy`, a `query` é buscada de forma aproximada e os outros padrões são
+    /// desconsiderados.
+    ///
+    /// Com `--smart-case`, a busca só diferencia maiúsculas e minúsculas se algum padrão
+    /// tiver alguma letra maiúscula.
+    pub fn new(config: &Config) -> Result<Self, regex::Error> {
+        Ok(Self {
+            kind: Kind::new(config)?,
+            expand: config.regex,
+        })
+    }
+}
+
+impl Kind {
+    fn new(config: &Config) -> Result<Self, regex::Error> {
+        let patterns: Vec<&str> = config.patterns().collect();
+        let case_sensitive = if config.smart_case {
+            patterns
+                .iter()
+                .any(|pattern| has_uppercase(pattern, config.regex))
+        } else {
+            config.case_sensitive
+        };
+
+        if let Some(max_distance) = config.fuzzy {
+            return Ok(Kind::Fuzzy(FuzzyQuery::new(
+                &config.query,
+                max_distance,
+                case_sensitive,
+                config.ignore_accents,
+            )));
+        }
+
+        if !config.regex && !config.word {
+            match patterns.as_slice() {
+                [query] if !case_sensitive => return Ok(Kind::CaseFold(FoldedQuery::new(query))),
+                [query] if query.len() >= HORSPOOL_MIN_LEN => {
+                    return Ok(Kind::Horspool(Horspool::new(query)))
+                }
+                [query] => return Ok(Kind::Literal(query.to_string())),
+                _ if case_sensitive => return Ok(Kind::Multi(AhoCorasick::new(patterns))),
+                _ => {}
+            }
+        }
+
+        let pattern = patterns
+            .iter()
+            .map(|pattern| {
+                let pattern = if config.regex {
+                    pattern.to_string()
+                } else {
+                    regex::escape(pattern)
+                };
+                if patterns.len() > 1 {
+                    format!("(?:{pattern})")
+                } else {
+                    pattern
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+        let pattern = if config.word {
+            format!(r"\b(?:{pattern})\b")
+        } else {
+            pattern
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map(Kind::Regex)
+    }
+}
+
+impl Matcher for PatternMatcher {
+    fn is_match(&self, line: &str) -> bool {
+        match &self.kind {
+            Kind::Literal(query) => line.contains(query.as_str()),
+            Kind::Horspool(query) => query.is_match(line),
+            Kind::CaseFold(query) => query.is_match(line),
+            Kind::Multi(patterns) => patterns.is_match(line),
+            Kind::Fuzzy(query) => query.is_match(line),
+            Kind::Regex(re) => re.is_match(line),
+        }
+    }
+
+    fn find_iter(&self, line: &str) -> Vec<Range<usize>> {
+        match &self.kind {
+            // Uma query vazia casa com toda linha, mas não tem o que destacar
+            Kind::Literal(query) if query.is_empty() => Vec::new(),
+            Kind::Literal(query) => line
+                .match_indices(query.as_str())
+                .map(|(start, found)| start..start + found.len())
+                .collect(),
+            Kind::Horspool(query) => query.find_iter(line),
+            Kind::CaseFold(query) => query.find_iter(line),
+            Kind::Multi(patterns) => patterns.find_iter(line),
+            Kind::Fuzzy(query) => query.find_iter(line),
+            Kind::Regex(re) => re
+                .find_iter(line)
+                .filter(|found| !found.is_empty())
+                .map(|found| found.range())
+                .collect(),
+        }
+    }
+
+    /// Com `--regex`, as referências `$1`, `$nome` ou `${nome}` do `template` são trocadas
+    /// pelos grupos de captura; sem ela, o `template` é literal.
+    fn replace(&self, line: &str, template: &str) -> (String, Vec<Range<usize>>) {
+        let Kind::Regex(re) = &self.kind else {
+            return replace_literal(self, line, template);
+        };
+        if !self.expand {
+            return replace_literal(self, line, template);
+        }
+
+        let mut replaced = String::with_capacity(line.len());
+        let mut spans = Vec::new();
+        let mut last = 0;
+        for captures in re.captures_iter(line) {
+            // O grupo 0 é a ocorrência inteira; ocorrências vazias não são trocadas
+            let Some(found) = captures.get(0).filter(|found| !found.is_empty()) else {
+                continue;
+            };
+            replaced.push_str(&line[last..found.start()]);
+            let start = replaced.len();
+            captures.expand(template, &mut replaced);
+            spans.push(start..replaced.len());
+            last = found.end();
+        }
+
+        replaced.push_str(&line[last..]);
+        (replaced, spans)
+    }
+}
+
+/// Procura uma letra maiúscula na query. Em uma expressão regular, o caractere depois de
+/// `\` é ignorado, já que `\D` ou `\W` não são letras.
+fn has_uppercase(query: &str, regex: bool) -> bool {
+    let mut escaped = false;
+    query.chars().any(|c| {
+        let is_upper = !escaped && c.is_uppercase();
+        escaped = regex && !escaped && c == '\\';
+        is_upper
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_spans_in_original_line() {
+        let config = Config {
+            query: "prova".to_string(),
+            ..Default::default()
+        };
+        let matcher = PatternMatcher::new(&config).unwrap();
+
+        assert_eq!(
+            vec![18..23, 24..29],
+            matcher.find_iter("14 23/03 Revisão Prova PROVA")
+        );
+    }
+
+    #[test]
+    fn should_find_literal_spans() {
+        let config = Config {
+            query: "Teste".to_string(),
+            case_sensitive: true,
+            ..Default::default()
+        };
+        let matcher = PatternMatcher::new(&config).unwrap();
+
+        assert_eq!(
+            vec![8..13, 17..22],
+            matcher.find_iter("7 23/02 Teste de Teste")
+        );
+        assert!(matcher.find_iter("02/03 Recesso").is_empty());
+    }
+
+    #[test]
+    fn should_use_smart_case() {
+        let smart = |query: &str| Config {
+            query: query.to_string(),
+            case_sensitive: true,
+            smart_case: true,
+            ..Default::default()
+        };
+
+        let matcher = PatternMatcher::new(&smart("prova")).unwrap();
+        assert!(matcher.is_match("15 24/03 PROVA 1"));
+
+        let matcher = PatternMatcher::new(&smart("Prova")).unwrap();
+        assert!(!matcher.is_match("15 24/03 PROVA 1"));
+        assert!(matcher.is_match("15 24/03 Prova 1"));
+    }
+
+    #[test]
+    fn should_pick_algorithm() {
+        let config = |query: &str, patterns: &[&str], case_sensitive| Config {
+            query: query.to_string(),
+            patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+            case_sensitive,
+            ..Default::default()
+        };
+
+        let kind = |config| PatternMatcher::new(&config).unwrap().kind;
+        assert!(matches!(kind(config("Prova", &[], true)), Kind::Literal(_)));
+        assert!(matches!(
+            kind(config("Teste Funcional", &[], true)),
+            Kind::Horspool(_)
+        ));
+        assert!(matches!(
+            kind(config("Prova", &["Recesso"], true)),
+            Kind::Multi(_)
+        ));
+        assert!(matches!(
+            kind(config("Prova", &["Recesso"], false)),
+            Kind::Regex(_)
+        ));
+    }
+
+    #[test]
+    fn should_match_any_pattern() {
+        let config = |case_sensitive| Config {
+            query: "Prova".to_string(),
+            patterns: vec!["Recesso".to_string(), "a.b".to_string()],
+            case_sensitive,
+            ..Default::default()
+        };
+
+        for case_sensitive in [true, false] {
+            let matcher = PatternMatcher::new(&config(case_sensitive)).unwrap();
+            assert!(matcher.is_match("02/03 Recesso"));
+            assert!(matcher.is_match("15 24/03 Prova 1"));
+            assert!(matcher.is_match("a.b"));
+            assert!(!matcher.is_match("acb"));
+            assert_eq!(vec![0..7, 10..15], matcher.find_iter("Recesso e Prova"));
+        }
+
+        let matcher = PatternMatcher::new(&config(false)).unwrap();
+        assert!(matcher.is_match("02/03 RECESSO"));
+    }
+
+    #[test]
+    fn should_replace_with_captures() {
+        let config = Config {
+            query: r"(?<dia>\d{2})/(\d{2})".to_string(),
+            case_sensitive: true,
+            regex: true,
+            ..Default::default()
+        };
+        let matcher = PatternMatcher::new(&config).unwrap();
+
+        let (line, spans) = matcher.replace("15 24/03 Prova 1", "$2/${dia}");
+        assert_eq!("15 03/24 Prova 1", line);
+        assert_eq!(
+            vec![(3, 8)],
+            spans.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>()
+        );
+
+        let matcher = PatternMatcher::new(&Config {
+            query: "24/03".to_string(),
+            case_sensitive: true,
+            word: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let (line, _) = matcher.replace("15 24/03 Prova 1", "$2");
+        assert_eq!("15 $2 Prova 1", line);
+    }
+
+    #[test]
+    fn should_replace_literal() {
+        let matcher = PatternMatcher::new(&Config {
+            query: "prova".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let (line, spans) = matcher.replace("Revisão Prova e PROVA", "P$1");
+        assert_eq!("Revisão P$1 e P$1", line);
+        assert_eq!(vec![9..12, 15..18], spans);
+    }
+
+    #[test]
+    fn should_ignore_escapes_in_smart_case() {
+        assert!(!has_uppercase(r"\d+ \W", true));
+        assert!(has_uppercase(r"\d+ Prova", true));
+        assert!(has_uppercase(r"\W", false));
+    }
+}
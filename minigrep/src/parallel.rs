@@ -0,0 +1,302 @@
+//! Executor que distribui a busca entre várias threads, entregando os resultados em ordem
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::mem;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed};
+use std::sync::mpsc::{self, SyncSender};
+use std::thread;
+
+/// Tamanho dos pedaços de saída enviados por um [`ChunkWriter`]
+const CHUNK_SIZE: usize = 64 * 1024;
+/// Pedaços que um item pode deixar prontos antes de esperar a sua vez de ser impresso
+const PENDING_CHUNKS: usize = 4;
+
+/// Quantidade de threads usada quando o [`Config`](crate::Config) não define uma.
+pub(crate) fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// Executa `job` para cada item de `items` em até `threads` threads e entrega os resultados
+/// para `consume` na mesma ordem dos itens, assim que cada um (e todos os anteriores)
+/// ficam prontos.
+///
+/// As threads pegam o próximo item de uma fila compartilhada e enviam o resultado por um
+/// canal para a thread que chamou a função, que reordena e consome. Se `consume` falhar,
+/// nenhum item novo é iniciado e o erro é retornado.
+pub(crate) fn for_each_ordered<T, R, E, J, C>(
+    items: &[T],
+    threads: usize,
+    job: J,
+    mut consume: C,
+) -> Result<(), E>
+where
+    T: Sync,
+    R: Send,
+    J: Fn(&T) -> R + Sync,
+    C: FnMut(R) -> Result<(), E>,
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, items.len().max(1)) {
+            let sender = sender.clone();
+            let (job, next, stop) = (&job, &next, &stop);
+            scope.spawn(move || {
+                while !stop.load(Relaxed) {
+                    let index = next.fetch_add(1, Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, job(item))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&expected) {
+                expected += 1;
+                if let Err(err) = consume(result) {
+                    stop.store(true, Relaxed);
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// O que [`for_each_streamed`] entrega, item por item
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Streamed<R> {
+    /// Um pedaço da saída do item; `first` só no primeiro pedaço de cada item
+    Output { chunk: Vec<u8>, first: bool },
+    /// O resultado do item, depois de toda a sua saída
+    Done(R),
+}
+
+enum Message<R> {
+    Chunk(Vec<u8>),
+    Done(R),
+}
+
+/// Saída de um item em [`for_each_streamed`], enviada em pedaços de até [`CHUNK_SIZE`].
+///
+/// Quando o item ainda não é o próximo a ser impresso e já tem [`PENDING_CHUNKS`] pedaços
+/// prontos, a escrita espera, então a memória usada não depende do tamanho da saída.
+pub(crate) struct ChunkWriter<R> {
+    buffer: Vec<u8>,
+    sender: SyncSender<Message<R>>,
+}
+
+impl<R> ChunkWriter<R> {
+    fn send(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = mem::take(&mut self.buffer);
+        self.sender
+            .send(Message::Chunk(chunk))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+impl<R> Write for ChunkWriter<R> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= CHUNK_SIZE {
+            self.send()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Como [`for_each_ordered`], mas cada `job` escreve a sua saída em um [`ChunkWriter`] e
+/// `consume` recebe os pedaços enquanto o job ainda roda, na ordem dos itens, seguidos do
+/// resultado do job.
+///
+/// Cada thread cria um canal limitado para o item que pegou e o envia para a thread que
+/// chamou a função, que lê os canais um de cada vez. Como os itens são pegos em ordem, o
+/// próximo item a ser impresso está sempre pronto ou em andamento, e as outras threads só
+/// esperam por ele quando os seus canais enchem.
+pub(crate) fn for_each_streamed<T, R, E, J, C>(
+    items: &[T],
+    threads: usize,
+    job: J,
+    mut consume: C,
+) -> Result<(), E>
+where
+    T: Sync,
+    R: Send,
+    J: Fn(&T, &mut ChunkWriter<R>) -> R + Sync,
+    C: FnMut(Streamed<R>) -> Result<(), E>,
+{
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, items.len().max(1)) {
+            let sender = sender.clone();
+            let (job, next, stop) = (&job, &next, &stop);
+            scope.spawn(move || {
+                while !stop.load(Relaxed) {
+                    let index = next.fetch_add(1, Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let (chunks, messages) = mpsc::sync_channel(PENDING_CHUNKS);
+                    if sender.send((index, messages)).is_err() {
+                        break;
+                    }
+                    let mut writer = ChunkWriter {
+                        buffer: Vec::new(),
+                        sender: chunks,
+                    };
+                    let result = job(item, &mut writer);
+                    // Um erro aqui só acontece se a thread principal desistiu da busca
+                    let _ = writer.send();
+                    let _ = writer.sender.send(Message::Done(result));
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+        for (index, messages) in receiver {
+            pending.insert(index, messages);
+            while let Some(messages) = pending.remove(&expected) {
+                expected += 1;
+                let mut first = true;
+                for message in messages {
+                    let streamed = match message {
+                        Message::Chunk(chunk) => Streamed::Output {
+                            chunk,
+                            first: mem::replace(&mut first, false),
+                        },
+                        Message::Done(result) => Streamed::Done(result),
+                    };
+                    if let Err(err) = consume(streamed) {
+                        stop.store(true, Relaxed);
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn should_consume_in_order() {
+        let items: Vec<u64> = (0..32).rev().collect();
+        let mut consumed = Vec::new();
+
+        let result: Result<(), ()> = for_each_ordered(
+            &items,
+            4,
+            |item| {
+                thread::sleep(Duration::from_millis(*item));
+                item * 2
+            },
+            |result| {
+                consumed.push(result);
+                Ok(())
+            },
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            items.iter().map(|item| item * 2).collect::<Vec<_>>(),
+            consumed
+        );
+    }
+
+    #[test]
+    fn should_stop_on_error() {
+        let items: Vec<usize> = (0..1000).collect();
+        let started = AtomicUsize::new(0);
+
+        let result = for_each_ordered(
+            &items,
+            2,
+            |item| {
+                started.fetch_add(1, Relaxed);
+                thread::sleep(Duration::from_millis(1));
+                *item
+            },
+            |result| if result == 3 { Err(result) } else { Ok(()) },
+        );
+
+        assert_eq!(Err(3), result);
+        assert!(started.load(Relaxed) < items.len());
+    }
+
+    #[test]
+    fn should_stream_chunks_in_order() {
+        let items: Vec<u8> = vec![b'a', b'b', b'c'];
+        let mut output = Vec::new();
+        let mut results = Vec::new();
+
+        let result: Result<(), ()> = for_each_streamed(
+            &items,
+            3,
+            |item, writer| {
+                // Maior que os pedaços que cabem no canal, então precisa esperar a vez
+                let line = [*item; 1000];
+                for _ in 0..(CHUNK_SIZE * (PENDING_CHUNKS + 2)) / line.len() {
+                    writer.write_all(&line).unwrap();
+                }
+                *item
+            },
+            |streamed| {
+                match streamed {
+                    Streamed::Output { chunk, first } => {
+                        assert_eq!(first, output.last() != Some(&chunk[0]));
+                        output.extend(chunk);
+                    }
+                    Streamed::Done(item) => results.push(item),
+                }
+                Ok(())
+            },
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(items, results);
+        let firsts: Vec<_> = output
+            .iter()
+            .enumerate()
+            .filter(|(index, byte)| *index == 0 || output[index - 1] != **byte)
+            .map(|(_, byte)| *byte)
+            .collect();
+        assert_eq!(items, firsts);
+    }
+
+    #[test]
+    fn should_accept_no_items() {
+        let result: Result<(), ()> = for_each_ordered(&[] as &[u8], 8, |_| (), |_| Ok(()));
+
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,262 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// Estado inicial do autômato
+const ROOT: u32 = 0;
+
+/// Busca de vários padrões literais ao mesmo tempo com o algoritmo de Aho-Corasick.
+///
+/// Os padrões formam uma trie de bytes com links de falha: cada byte da linha é lido uma
+/// única vez, não importa quantos padrões existam. Só a raiz tem uma tabela com os 256
+/// bytes; os outros estados guardam apenas os seus filhos e seguem o link de falha para os
+/// demais bytes, então a memória cresce com o tamanho dos padrões, e não 1 KiB por estado.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    /// Próximo estado da raiz para cada byte
+    root: Vec<u32>,
+    /// Filhos de cada estado na trie, ordenados pelo byte
+    children: Vec<Vec<(u8, u32)>>,
+    /// Estado do maior sufixo próprio de cada estado que também está na trie
+    failures: Vec<u32>,
+    /// Tamanho, em bytes, do padrão que termina em cada estado
+    lengths: Vec<Option<usize>>,
+    /// Próximo estado na cadeia de falhas em que termina algum padrão
+    outputs: Vec<Option<u32>>,
+    /// Se algum padrão termina em cada estado, ele mesmo ou pela cadeia de falhas
+    matching: Vec<bool>,
+    /// Um padrão vazio casa com qualquer linha
+    matches_empty: bool,
+}
+
+impl AhoCorasick {
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        let mut automaton = Self {
+            root: vec![ROOT; 256],
+            children: vec![Vec::new()],
+            failures: Vec::new(),
+            lengths: vec![None],
+            outputs: vec![None],
+            matching: Vec::new(),
+            matches_empty: false,
+        };
+        for pattern in patterns {
+            automaton.insert(pattern.as_ref().as_bytes());
+        }
+        automaton.link();
+        automaton
+    }
+
+    /// Adiciona `pattern` à trie.
+    fn insert(&mut self, pattern: &[u8]) {
+        if pattern.is_empty() {
+            self.matches_empty = true;
+            return;
+        }
+
+        let mut state = ROOT;
+        for &byte in pattern {
+            let children = &mut self.children[state as usize];
+            state = match children.binary_search_by_key(&byte, |&(child_byte, _)| child_byte) {
+                Ok(index) => children[index].1,
+                Err(index) => {
+                    let child = self.lengths.len() as u32;
+                    children.insert(index, (byte, child));
+                    self.children.push(Vec::new());
+                    self.lengths.push(None);
+                    self.outputs.push(None);
+                    child
+                }
+            };
+        }
+        self.lengths[state as usize] = Some(pattern.len());
+    }
+
+    /// Preenche a tabela da raiz e calcula os links de falha em largura, já que o link de
+    /// um estado depende do link do seu pai.
+    fn link(&mut self) {
+        self.failures = vec![ROOT; self.lengths.len()];
+        self.matching = vec![false; self.lengths.len()];
+        let mut queue = VecDeque::new();
+
+        for &(byte, child) in &self.children[ROOT as usize] {
+            self.root[byte as usize] = child;
+            queue.push_back(child);
+        }
+
+        while let Some(state) = queue.pop_front() {
+            let failure = self.failures[state as usize] as usize;
+            self.outputs[state as usize] = if self.lengths[failure].is_some() {
+                Some(failure as u32)
+            } else {
+                self.outputs[failure]
+            };
+            self.matching[state as usize] =
+                self.lengths[state as usize].is_some() || self.outputs[state as usize].is_some();
+
+            for index in 0..self.children[state as usize].len() {
+                let (byte, child) = self.children[state as usize][index];
+                self.failures[child as usize] = self.next(failure as u32, byte);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        if self.matches_empty {
+            return true;
+        }
+
+        let mut state = ROOT;
+        haystack.bytes().any(|byte| {
+            state = self.next(state, byte);
+            self.matching[state as usize]
+        })
+    }
+
+    /// Intervalos, em bytes, das ocorrências em `haystack`, escolhidas como no `grep`: a que
+    /// começa primeiro e, entre elas, a mais longa, sem sobreposição.
+    ///
+    /// Padrões vazios não produzem ocorrências.
+    pub fn find_iter(&self, haystack: &str) -> Vec<Range<usize>> {
+        let mut found = Vec::new();
+        let mut state = ROOT;
+        for (index, byte) in haystack.bytes().enumerate() {
+            state = self.next(state, byte);
+
+            let mut output = match self.lengths[state as usize] {
+                Some(_) => Some(state),
+                None => self.outputs[state as usize],
+            };
+            while let Some(matched) = output {
+                let length = self.lengths[matched as usize].unwrap_or_default();
+                found.push(index + 1 - length..index + 1);
+                output = self.outputs[matched as usize];
+            }
+        }
+
+        found.sort_unstable_by_key(|span| (span.start, Reverse(span.end)));
+        let mut end = 0;
+        found.retain(|span| {
+            let keep = span.start >= end;
+            if keep {
+                end = span.end;
+            }
+            keep
+        });
+        found
+    }
+
+    fn next(&self, mut state: u32, byte: u8) -> u32 {
+        loop {
+            if state == ROOT {
+                return self.root[byte as usize];
+            }
+            let children = &self.children[state as usize];
+            if let Ok(index) = children.binary_search_by_key(&byte, |&(child_byte, _)| child_byte) {
+                return children[index].1;
+            }
+            state = self.failures[state as usize];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(patterns: &[&str], haystack: &str) -> Vec<(usize, usize)> {
+        AhoCorasick::new(patterns)
+            .find_iter(haystack)
+            .into_iter()
+            .map(|span| (span.start, span.end))
+            .collect()
+    }
+
+    #[test]
+    fn should_find_many_patterns() {
+        let automaton = AhoCorasick::new(["Prova", "Recesso", "Feriado"]);
+
+        assert!(automaton.is_match("02/03 Recesso"));
+        assert!(automaton.is_match("15 24/03 Prova 1"));
+        assert!(!automaton.is_match("9 03/03 Teste Funcional"));
+        assert_eq!(
+            vec![(0, 7), (10, 15)],
+            spans(&["Prova", "Recesso"], "Recesso e Prova")
+        );
+    }
+
+    #[test]
+    fn should_follow_failure_links() {
+        assert_eq!(
+            vec![(1, 4), (4, 8)],
+            spans(&["he", "she", "his", "hers"], "ahishers")
+        );
+        assert_eq!(vec![(1, 4)], spans(&["abcd", "bcx", "cxy"], "abcxyz"));
+    }
+
+    #[test]
+    fn should_prefer_leftmost_longest() {
+        assert_eq!(vec![(0, 4)], spans(&["bc", "abcd", "abc"], "abcd"));
+        assert_eq!(vec![(0, 2), (3, 4)], spans(&["ab", "bcd", "d"], "abcd"));
+    }
+
+    #[test]
+    fn should_match_empty_pattern() {
+        let automaton = AhoCorasick::new(["", "Prova"]);
+
+        assert!(automaton.is_match("9 03/03 Teste Funcional"));
+        assert!(automaton.find_iter("9 03/03 Teste Funcional").is_empty());
+        assert!(!AhoCorasick::new([] as [&str; 0]).is_match("Prova"));
+    }
+
+    #[test]
+    fn should_agree_with_contains_on_many_patterns() {
+        let words = [
+            "Prova", "Recesso", "Teste", "Feriado", "Revisão", "ão", "Pro", "sso 1",
+        ];
+        let patterns: Vec<_> = (0..2000)
+            .map(|index| format!("{} {}", words[index % words.len()], index / 7))
+            .chain(words.map(str::to_string))
+            .collect();
+        let automaton = AhoCorasick::new(&patterns);
+
+        for haystack in [
+            "02/03 Recesso 1",
+            "15 24/03 Prova 100",
+            "Revisão 285 e Feriado",
+            "9 03/03 Funcional",
+            "Pr Recess Tes",
+        ] {
+            assert_eq!(
+                patterns
+                    .iter()
+                    .any(|pattern| haystack.contains(pattern.as_str())),
+                automaton.is_match(haystack),
+                "{haystack}"
+            );
+        }
+        assert_eq!(
+            vec![(0, 9), (10, 19)],
+            spans(
+                &["Recesso", "Recesso 1", "Prova 10", "Prova 100"],
+                "Recesso 1 Prova 100"
+            )
+        );
+    }
+
+    #[test]
+    fn should_find_multibyte_patterns() {
+        assert_eq!(
+            vec![(9, 23), (26, 35)],
+            spans(
+                &["Apresentação", "Métricas"],
+                "12 16/03 Apresentação e Métricas"
+            )
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::ops::Range;
+
+/// Busca de um padrão literal com o algoritmo de Boyer-Moore-Horspool.
+///
+/// O padrão é comparado de trás para frente e, a cada falha, a janela avança de acordo com
+/// o último byte dela: quanto maior o padrão, maiores os saltos, então é mais rápido que a
+/// busca ingênua para padrões longos.
+#[derive(Debug, Clone)]
+pub struct Horspool {
+    needle: Vec<u8>,
+    /// Quanto a janela avança quando termina em cada byte
+    shift: Box<[usize; 256]>,
+}
+
+impl Horspool {
+    pub fn new(needle: &str) -> Self {
+        let needle = needle.as_bytes().to_vec();
+        let mut shift = Box::new([needle.len(); 256]);
+        for (index, &byte) in needle
+            .iter()
+            .enumerate()
+            .take(needle.len().saturating_sub(1))
+        {
+            shift[byte as usize] = needle.len() - 1 - index;
+        }
+
+        Self { needle, shift }
+    }
+
+    /// Posição, em bytes, da primeira ocorrência do padrão em `haystack` a partir de `start`.
+    pub fn find_at(&self, haystack: &str, start: usize) -> Option<usize> {
+        let haystack = haystack.as_bytes();
+        let last = self.needle.len().checked_sub(1)?;
+        let mut position = start;
+
+        while position + last < haystack.len() {
+            let window = &haystack[position..=position + last];
+            if window.iter().rev().eq(self.needle.iter().rev()) {
+                return Some(position);
+            }
+            position += self.shift[window[last] as usize];
+        }
+
+        None
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.find_at(haystack, 0).is_some()
+    }
+
+    /// Intervalos, em bytes, de todas as ocorrências não sobrepostas em `haystack`.
+    ///
+    /// Como o padrão é UTF-8 válido, as ocorrências sempre começam e terminam em fronteiras
+    /// de caracteres.
+    pub fn find_iter(&self, haystack: &str) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        let mut start = 0;
+        while let Some(found) = self.find_at(haystack, start) {
+            start = found + self.needle.len();
+            spans.push(found..start);
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_occurrences() {
+        let horspool = Horspool::new("Teste Funcional");
+        let haystack = "8 24/02 Teste Funcional, 9 03/03 Teste Funcional";
+
+        assert_eq!(Some(8), horspool.find_at(haystack, 0));
+        assert_eq!(vec![8..23, 33..48], horspool.find_iter(haystack));
+        assert!(!horspool.is_match("10 09/03 Teste Estrutural"));
+    }
+
+    #[test]
+    fn should_not_overlap() {
+        let spans: Vec<_> = Horspool::new("aa")
+            .find_iter("aaaaa")
+            .into_iter()
+            .map(|span| (span.start, span.end))
+            .collect();
+
+        assert_eq!(vec![(0, 2), (2, 4)], spans);
+    }
+
+    #[test]
+    fn should_find_multibyte_needle() {
+        let horspool = Horspool::new("Apresentação");
+
+        assert_eq!(Some(9), horspool.find_at("12 16/03 Apresentação TP 1", 0));
+        assert_eq!(None, horspool.find_at("Apresentacao", 0));
+    }
+
+    #[test]
+    fn should_not_match_empty_needle() {
+        let horspool = Horspool::new("");
+
+        assert!(!horspool.is_match("qualquer linha"));
+        assert!(horspool.find_iter("qualquer linha").is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+//! Algoritmos de busca de padrões literais, usados pelo matcher do minigrep
+//!
+//! - [`Horspool`]: Boyer-Moore-Horspool, para uma query literal longa;
+//! - [`AhoCorasick`]: autômato que busca vários padrões em uma única passada pela linha.
+
+mod aho_corasick;
+mod horspool;
+
+pub use aho_corasick::AhoCorasick;
+pub use horspool::Horspool;
@@ -0,0 +1,196 @@
+//! Formatação da saída em texto, com cores ANSI opcionais
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::sink::{Output, SearchLine, Sink, Stats};
+use crate::Config;
+
+const COLOR_MATCH: &str = "\x1b[1;31m";
+const COLOR_PATH: &str = "\x1b[35m";
+const COLOR_NUMBER: &str = "\x1b[32m";
+const COLOR_SEPARATOR: &str = "\x1b[36m";
+const COLOR_RESET: &str = "\x1b[0m";
+
+pub(crate) struct Printer<W> {
+    out: W,
+    color: bool,
+    /// Prefixa cada linha com o caminho do arquivo
+    show_path: bool,
+    line_number: bool,
+    byte_offset: bool,
+    /// Imprime só a quantidade de linhas de cada arquivo (`-c`)
+    count: bool,
+    /// Imprime só o caminho dos arquivos com alguma linha encontrada (`-l`)
+    files_with_matches: bool,
+}
+
+impl<W: Write> Printer<W> {
+    pub fn new(out: W, config: &Config, color: bool, show_path: bool) -> Self {
+        Self {
+            out,
+            color,
+            show_path,
+            line_number: config.line_number,
+            byte_offset: config.byte_offset,
+            count: config.count,
+            files_with_matches: config.files_with_matches,
+        }
+    }
+
+    /// Imprime uma linha, com os prefixos separados por `separator`
+    fn line(&mut self, path: &Path, line: &SearchLine, separator: char) -> io::Result<()> {
+        if self.show_path {
+            self.prefix(COLOR_PATH, &path.display().to_string(), separator)?;
+        }
+        if self.line_number {
+            self.prefix(COLOR_NUMBER, &line.number.to_string(), separator)?;
+        }
+        if self.byte_offset {
+            let offsets = if line.spans.is_empty() {
+                line.offset.to_string()
+            } else {
+                line.spans
+                    .iter()
+                    .map(|span| (line.offset + line.byte_position(span.start)).to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            };
+            self.prefix(COLOR_NUMBER, &offsets, separator)?;
+        }
+
+        if !self.color {
+            return writeln!(self.out, "{}", line.text);
+        }
+
+        let mut last = 0;
+        for span in &line.spans {
+            write!(
+                self.out,
+                "{}{}{}{}",
+                &line.text[last..span.start],
+                COLOR_MATCH,
+                &line.text[span.clone()],
+                COLOR_RESET
+            )?;
+            last = span.end;
+        }
+        writeln!(self.out, "{}", &line.text[last..])
+    }
+
+    fn prefix(&mut self, color: &str, text: &str, separator: char) -> io::Result<()> {
+        self.colored(color, text)?;
+        self.colored(COLOR_SEPARATOR, &separator.to_string())
+    }
+
+    fn colored(&mut self, color: &str, text: &str) -> io::Result<()> {
+        if self.color {
+            write!(self.out, "{}{}{}", color, text, COLOR_RESET)
+        } else {
+            write!(self.out, "{}", text)
+        }
+    }
+}
+
+impl<W: Write> Sink for Printer<W> {
+    fn matched(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        self.line(path, line, ':')?;
+        Ok(true)
+    }
+
+    fn context(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        self.line(path, line, '-')?;
+        Ok(true)
+    }
+
+    fn context_break(&mut self) -> io::Result<()> {
+        self.separator()
+    }
+
+    fn end(&mut self, path: &Path, stats: &Stats) -> io::Result<()> {
+        if self.files_with_matches {
+            if stats.matched_lines > 0 {
+                self.colored(COLOR_PATH, &path.display().to_string())?;
+                writeln!(self.out)?;
+            }
+        } else if self.count {
+            if self.show_path {
+                self.prefix(COLOR_PATH, &path.display().to_string(), ':')?;
+            }
+            writeln!(self.out, "{}", stats.matched_lines)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Output for Printer<W> {
+    fn separator(&mut self) -> io::Result<()> {
+        self.colored(COLOR_SEPARATOR, "--")?;
+        writeln!(self.out)
+    }
+
+    fn write_raw(&mut self, output: &[u8]) -> io::Result<()> {
+        self.out.write_all(output)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printer(color: bool, byte_offset: bool, show_path: bool) -> Printer<Vec<u8>> {
+        let config = Config {
+            line_number: true,
+            byte_offset,
+            ..Default::default()
+        };
+        Printer::new(Vec::new(), &config, color, show_path)
+    }
+
+    fn line() -> SearchLine {
+        SearchLine {
+            number: 17,
+            offset: 400,
+            text: "15 24/03 Prova 1 e Prova 2".to_string(),
+            spans: vec![9..14, 19..24],
+            invalid: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn should_print_plain_line() {
+        let mut printer = printer(false, false, true);
+        printer.matched(Path::new("a.txt"), &line()).unwrap();
+
+        assert_eq!(
+            "a.txt:17:15 24/03 Prova 1 e Prova 2\n",
+            String::from_utf8(printer.out).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_print_byte_offsets() {
+        let mut printer = printer(false, true, false);
+        printer.matched(Path::new("a.txt"), &line()).unwrap();
+
+        assert_eq!(
+            "17:409,419:15 24/03 Prova 1 e Prova 2\n",
+            String::from_utf8(printer.out).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_highlight_matches() {
+        let mut printer = printer(true, false, false);
+        printer.matched(Path::new("a.txt"), &line()).unwrap();
+
+        assert_eq!(
+            "\x1b[32m17\x1b[0m\x1b[36m:\x1b[0m15 24/03 \x1b[1;31mProva\x1b[0m 1 e \x1b[1;31mProva\x1b[0m 2\n",
+            String::from_utf8(printer.out).unwrap()
+        );
+    }
+}
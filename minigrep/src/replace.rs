@@ -0,0 +1,223 @@
+//! Substituição das ocorrências nos arquivos (`--replace` com `--in-place`)
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::{parallel, walk, Config, Input, Matcher, PatternMatcher};
+
+/// Uma linha alterada pela substituição, numerada a partir de 1
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Change {
+    pub number: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// Substitui as ocorrências em cada linha de `contents` por `template`, mantendo os
+/// terminadores de linha (`\n` ou `\r\n`) originais. Retorna o novo conteúdo e as linhas
+/// alteradas.
+pub(crate) fn replace_contents<M: Matcher>(
+    matcher: &M,
+    template: &str,
+    contents: &str,
+) -> (String, Vec<Change>) {
+    let mut replaced = String::with_capacity(contents.len());
+    let mut changes = Vec::new();
+
+    for (index, line) in contents.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches('\n').trim_end_matches('\r');
+        let ending = &line[text.len()..];
+
+        let (after, spans) = matcher.replace(text, template);
+        if spans.is_empty() || after == text {
+            replaced.push_str(line);
+            continue;
+        }
+
+        replaced.push_str(&after);
+        replaced.push_str(ending);
+        changes.push(Change {
+            number: index + 1,
+            before: text.to_string(),
+            after,
+        });
+    }
+
+    (replaced, changes)
+}
+
+/// Escreve `contents` em `path` de forma atômica: o conteúdo vai para um arquivo temporário
+/// no mesmo diretório, que depois substitui o original com um `rename`. Se algo falhar no
+/// meio do caminho, o arquivo original continua intacto.
+///
+/// Um link simbólico é seguido, e quem é reescrito é o arquivo para o qual ele aponta.
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let temporary = temporary_path(&path);
+    let result = (|| {
+        let mut file = File::create(&temporary)?;
+        file.write_all(contents.as_bytes())?;
+        file.set_permissions(fs::metadata(&path)?.permissions())?;
+        file.sync_all()?;
+        fs::rename(&temporary, &path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+/// Caminho do arquivo temporário de [`write_atomic`], oculto e ao lado de `path`, para que
+/// o `rename` não atravesse sistemas de arquivos.
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.minigrep-{}.tmp", name, process::id()))
+}
+
+/// Imprime as linhas alteradas de `path` como um diff unificado, uma seção por linha.
+pub(crate) fn print_diff<W: Write>(out: &mut W, path: &Path, changes: &[Change]) -> io::Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "--- {}", path.display())?;
+    writeln!(out, "+++ {}", path.display())?;
+    for change in changes {
+        writeln!(out, "@@ -{0} +{0} @@", change.number)?;
+        writeln!(out, "-{}", change.before)?;
+        writeln!(out, "+{}", change.after)?;
+    }
+    Ok(())
+}
+
+/// Reescreve cada arquivo de `inputs` com as substituições de `--replace`, ou, com
+/// `--dry-run`, apenas imprime o diff do que seria alterado.
+///
+/// Arquivos encontrados ao percorrer diretórios são ignorados se forem binários ou não
+/// forem UTF-8 válido.
+pub(crate) fn rewrite_all(
+    config: &Config,
+    matcher: &PatternMatcher,
+    inputs: &[Input],
+    threads: usize,
+) -> Result<(), Box<dyn Error>> {
+    if inputs.iter().any(|input| input.stdin) {
+        return Err("--in-place não pode ser usado com a entrada padrão".into());
+    }
+    let template = config.replace.as_deref().unwrap_or_default();
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    parallel::for_each_ordered(
+        inputs,
+        threads,
+        |input| {
+            let bytes = fs::read(&input.path)?;
+            if input.walked && walk::is_binary(&bytes) {
+                return Ok(Vec::new());
+            }
+            let contents = match String::from_utf8(bytes) {
+                Ok(contents) => contents,
+                Err(_) if input.walked => return Ok(Vec::new()),
+                Err(err) => return Err(io::Error::new(ErrorKind::InvalidData, err)),
+            };
+
+            let (replaced, changes) = replace_contents(matcher, template, &contents);
+            let mut diff = Vec::new();
+            if config.dry_run {
+                print_diff(&mut diff, &input.path, &changes)?;
+            } else if !changes.is_empty() {
+                write_atomic(&input.path, &replaced)?;
+            }
+            io::Result::Ok(diff)
+        },
+        |diff| out.write_all(&diff?),
+    )?;
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn matcher(query: &str, regex: bool) -> PatternMatcher {
+        PatternMatcher::new(&Config {
+            query: query.to_string(),
+            case_sensitive: true,
+            regex,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn should_keep_line_endings() {
+        let contents = "02/03 Recesso\r\n9 03/03 Teste Funcional\n14/04 Recesso";
+        let (replaced, changes) = replace_contents(&matcher("Recesso", false), "Feriado", contents);
+
+        assert_eq!(
+            "02/03 Feriado\r\n9 03/03 Teste Funcional\n14/04 Feriado",
+            replaced
+        );
+        assert_eq!(
+            vec![1, 3],
+            changes.iter().map(|c| c.number).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_print_diff() {
+        let (_, changes) = replace_contents(
+            &matcher(r"(\d{2})/(\d{2})", true),
+            "$2/$1",
+            "Aula\n02/03 Recesso\n",
+        );
+        let mut out = Vec::new();
+        print_diff(&mut out, Path::new("cronograma.txt"), &changes).unwrap();
+
+        assert_eq!(
+            "--- cronograma.txt\n+++ cronograma.txt\n@@ -2 +2 @@\n-02/03 Recesso\n+03/02 Recesso\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_write_atomically() {
+        let path = env::temp_dir().join(format!("minigrep-replace-{}.txt", process::id()));
+        fs::write(&path, "02/03 Recesso\n").unwrap();
+
+        write_atomic(&path, "02/03 Feriado\n").unwrap();
+
+        assert_eq!("02/03 Feriado\n", fs::read_to_string(&path).unwrap());
+        assert!(!temporary_path(&path).exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_rewrite_symlink_target() {
+        let dir = env::temp_dir().join(format!("minigrep-replace-link-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        let target = dir.join("cronograma.txt");
+        let link = dir.join("link.txt");
+        fs::write(&target, "02/03 Recesso\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, "02/03 Feriado\n").unwrap();
+
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!("02/03 Feriado\n", fs::read_to_string(&target).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
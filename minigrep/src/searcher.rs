@@ -0,0 +1,437 @@
+//! O motor de busca: lê as linhas, testa cada uma com um [`Matcher`] e entrega os
+//! resultados a um [`Sink`]
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::iter;
+use std::path::Path;
+
+use crate::context::{ContextWindow, Line};
+use crate::decompress;
+use crate::matcher::{Matcher, PatternMatcher};
+use crate::sink::{SearchLine, Sink, Stats};
+use crate::Config;
+
+/// Busca com um [`Matcher`] e entrega as linhas encontradas, as de contexto e as estatísticas
+/// de cada arquivo a um [`Sink`].
+///
+/// Das opções do [`Config`], usa só as que mudam quais linhas são entregues: `-v`, `-A`,
+/// `-B`, `-c`, `-l`, `--replace` e `-z`. A formatação fica a cargo do [`Sink`].
+///
+/// ### Exemplo
+/// ```
+/// use minigrep::{Config, SearchLine, Searcher};
+///
+/// let config = Config {
+///     query: "prova".to_string(),
+///     ..Default::default()
+/// };
+/// let searcher = Searcher::from_config(&config).unwrap();
+/// let mut lines: Vec<SearchLine> = Vec::new();
+///
+/// let contents = "14 23/03 Revisão Prova 1\n02/03 Recesso\n15 24/03 Prova 1";
+/// let stats = searcher
+///     .search_reader("cronograma.txt".as_ref(), contents.as_bytes(), &mut lines)
+///     .unwrap();
+///
+/// assert_eq!(2, stats.matched_lines);
+/// assert_eq!(3, lines[1].number);
+/// ```
+#[derive(Debug)]
+pub struct Searcher<M> {
+    matcher: M,
+    before_context: usize,
+    after_context: usize,
+    invert_match: bool,
+    replace: Option<String>,
+    /// Só conta as linhas, sem entregá-las ao [`Sink`] (`-c` e `-l`)
+    count_only: bool,
+    /// Para na primeira linha encontrada (`-l`)
+    first_only: bool,
+    search_zip: bool,
+}
+
+impl Searcher<PatternMatcher> {
+    /// Cria um [`Searcher`] com o [`PatternMatcher`] descrito pelo `config`.
+    pub fn from_config(config: &Config) -> Result<Self, regex::Error> {
+        Ok(Self::new(PatternMatcher::new(config)?, config))
+    }
+}
+
+impl<M: Matcher> Searcher<M> {
+    pub fn new(matcher: M, config: &Config) -> Self {
+        Self {
+            matcher,
+            before_context: config.before_context,
+            after_context: config.after_context,
+            invert_match: config.invert_match,
+            replace: config.replace.clone(),
+            count_only: config.count || config.files_with_matches,
+            first_only: config.files_with_matches,
+            search_zip: config.search_zip,
+        }
+    }
+
+    pub fn matcher(&self) -> &M {
+        &self.matcher
+    }
+
+    /// Busca no arquivo `path`, descomprimindo-o antes com `-z`.
+    pub fn search_path<S: Sink + ?Sized>(&self, path: &Path, sink: &mut S) -> io::Result<Stats> {
+        let reader = self.decompress(Box::new(BufReader::new(File::open(path)?)))?;
+        self.search_reader(path, reader, sink)
+    }
+
+    /// Busca em `reader`, linha a linha, e retorna as estatísticas da busca.
+    ///
+    /// `path` é só repassado ao `sink`; nada é lido dele. A busca para no fim de `reader`
+    /// ou quando o `sink` pede.
+    pub fn search_reader<R: BufRead, S: Sink + ?Sized>(
+        &self,
+        path: &Path,
+        reader: R,
+        sink: &mut S,
+    ) -> io::Result<Stats> {
+        let mut stats = Stats {
+            searches: 1,
+            ..Default::default()
+        };
+        sink.begin(path)?;
+
+        if self.count_only {
+            self.count_lines(reader, &mut stats)?;
+        } else {
+            self.search_lines(path, reader, sink, &mut stats)?;
+        }
+
+        stats.searches_with_match = usize::from(stats.matched_lines > 0);
+        sink.end(path, &stats)?;
+
+        Ok(stats)
+    }
+
+    /// Com `-z`, troca `reader` por um que descomprime o conteúdo.
+    pub(crate) fn decompress<'a>(
+        &self,
+        reader: Box<dyn BufRead + 'a>,
+    ) -> io::Result<Box<dyn BufRead + 'a>> {
+        if self.search_zip {
+            decompress::decompress(reader)
+        } else {
+            Ok(reader)
+        }
+    }
+
+    /// Só conta as linhas encontradas, para `-c` e `-l`.
+    fn count_lines<R: BufRead>(&self, reader: R, stats: &mut Stats) -> io::Result<()> {
+        for line in read_lines(reader) {
+            let line = line?;
+            let is_match = self.matcher.is_match(&line.text);
+            if is_match == self.invert_match {
+                continue;
+            }
+            stats.matched_lines += 1;
+            if is_match {
+                stats.matches += self.matcher.find_iter(&line.text).len();
+            }
+            if self.first_only {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Entrega as linhas encontradas em `reader` e as linhas de contexto ao redor delas.
+    fn search_lines<R: BufRead, S: Sink + ?Sized>(
+        &self,
+        path: &Path,
+        reader: R,
+        sink: &mut S,
+        stats: &mut Stats,
+    ) -> io::Result<()> {
+        let mut window = ContextWindow::new(self.before_context, self.after_context);
+        let mut lines = Vec::new();
+        for line in read_lines(reader) {
+            let mut line = line?;
+            let is_match = self.matcher.is_match(&line.text);
+            if let (true, Some(template)) = (is_match, &self.replace) {
+                (line.text, line.spans) = self.matcher.replace(&line.text, template);
+                // As posições passam a se referir ao texto substituído
+                line.invalid.clear();
+            } else if is_match {
+                line.spans = self.matcher.find_iter(&line.text);
+            }
+            window.push(line.number, line, is_match != self.invert_match, &mut lines);
+
+            for line in lines.drain(..) {
+                let more = match line {
+                    Line::Match(_, line) => {
+                        stats.matched_lines += 1;
+                        stats.matches += line.spans.len();
+                        sink.matched(path, &line)?
+                    }
+                    Line::Context(_, line) => sink.context(path, &line)?,
+                    Line::Separator => {
+                        sink.context_break()?;
+                        true
+                    }
+                };
+                if !more {
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Lê as linhas de `reader`, sem o `\n` (ou `\r\n`) final, guardando o número e a
+/// posição em bytes do início de cada uma.
+///
+/// Bytes que não são UTF-8 válido viram o caractere de substituição `�`, em vez de
+/// interromper a busca. As posições das linhas continuam se referindo aos bytes do
+/// arquivo, e as das ocorrências são convertidas com [`SearchLine::byte_position`].
+fn read_lines<R: BufRead>(mut reader: R) -> impl Iterator<Item = io::Result<SearchLine>> {
+    let mut number = 0;
+    let mut offset = 0;
+
+    iter::from_fn(move || {
+        let mut bytes = Vec::new();
+        match reader.read_until(b'\n', &mut bytes) {
+            Ok(0) => None,
+            Ok(read) => {
+                number += 1;
+                let line_offset = offset;
+                offset += read;
+                if bytes.ends_with(b"\n") {
+                    bytes.pop();
+                    if bytes.ends_with(b"\r") {
+                        bytes.pop();
+                    }
+                }
+
+                let (text, invalid) = match String::from_utf8(bytes) {
+                    Ok(text) => (text, Vec::new()),
+                    Err(err) => decode_lossy(err.as_bytes()),
+                };
+                Some(Ok(SearchLine {
+                    number,
+                    offset: line_offset,
+                    text,
+                    spans: Vec::new(),
+                    invalid,
+                }))
+            }
+            Err(err) => Some(Err(err)),
+        }
+    })
+}
+
+/// Como [`String::from_utf8_lossy`], mas também retorna a posição de cada `�` e quantos
+/// bytes ele substitui (veja [`SearchLine::invalid`]).
+fn decode_lossy(bytes: &[u8]) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(bytes.len());
+    let mut invalid = Vec::new();
+
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        if !chunk.invalid().is_empty() {
+            invalid.push((text.len(), chunk.invalid().len()));
+            text.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+
+    (text, invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENTS: &str = "\
+9 03/03 Teste Funcional
+02/03 Recesso
+10 09/03 Teste Estrutural
+15 24/03 Prova 1";
+
+    fn searcher(config: Config) -> Searcher<PatternMatcher> {
+        Searcher::from_config(&Config {
+            case_sensitive: true,
+            ..config
+        })
+        .unwrap()
+    }
+
+    /// Guarda cada chamada recebida, para conferir a ordem
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl Sink for Events {
+        fn begin(&mut self, path: &Path) -> io::Result<()> {
+            self.0.push(format!("begin {}", path.display()));
+            Ok(())
+        }
+
+        fn matched(&mut self, _path: &Path, line: &SearchLine) -> io::Result<bool> {
+            self.0.push(format!("match {}", line.number));
+            Ok(true)
+        }
+
+        fn context(&mut self, _path: &Path, line: &SearchLine) -> io::Result<bool> {
+            self.0.push(format!("context {}", line.number));
+            Ok(true)
+        }
+
+        fn context_break(&mut self) -> io::Result<()> {
+            self.0.push("--".to_string());
+            Ok(())
+        }
+
+        fn end(&mut self, _path: &Path, stats: &Stats) -> io::Result<()> {
+            self.0.push(format!("end {}", stats.matched_lines));
+            Ok(())
+        }
+    }
+
+    fn events(searcher: &Searcher<PatternMatcher>) -> Vec<String> {
+        let mut events = Events::default();
+        searcher
+            .search_reader(Path::new("a.txt"), CONTENTS.as_bytes(), &mut events)
+            .unwrap();
+        events.0
+    }
+
+    #[test]
+    fn should_report_matches_and_context() {
+        let searcher = searcher(Config {
+            query: "Teste".to_string(),
+            before_context: 1,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            vec!["begin a.txt", "match 1", "context 2", "match 3", "end 2"],
+            events(&searcher)
+        );
+    }
+
+    #[test]
+    fn should_break_between_groups() {
+        let searcher = searcher(Config {
+            query: "Funcional|Prova".to_string(),
+            regex: true,
+            after_context: 1,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            vec![
+                "begin a.txt",
+                "match 1",
+                "context 2",
+                "--",
+                "match 4",
+                "end 2"
+            ],
+            events(&searcher)
+        );
+    }
+
+    #[test]
+    fn should_only_count_lines() {
+        let searcher = searcher(Config {
+            query: "Teste".to_string(),
+            count: true,
+            ..Default::default()
+        });
+        let mut lines: Vec<SearchLine> = Vec::new();
+
+        let stats = searcher
+            .search_reader(Path::new("a.txt"), CONTENTS.as_bytes(), &mut lines)
+            .unwrap();
+
+        assert!(lines.is_empty());
+        assert_eq!(
+            Stats {
+                searches: 1,
+                searches_with_match: 1,
+                matched_lines: 2,
+                matches: 2
+            },
+            stats
+        );
+    }
+
+    #[test]
+    fn should_stop_when_sink_asks() {
+        struct First(Option<usize>);
+
+        impl Sink for First {
+            fn matched(&mut self, _path: &Path, line: &SearchLine) -> io::Result<bool> {
+                self.0 = Some(line.number);
+                Ok(false)
+            }
+        }
+
+        let searcher = searcher(Config {
+            query: "Teste".to_string(),
+            ..Default::default()
+        });
+        let mut first = First(None);
+
+        let stats = searcher
+            .search_reader(Path::new("a.txt"), CONTENTS.as_bytes(), &mut first)
+            .unwrap();
+
+        assert_eq!(Some(1), first.0);
+        assert_eq!(1, stats.matched_lines);
+    }
+
+    #[test]
+    fn should_read_invalid_utf8_lossily() {
+        let contents: &[u8] = b"9 03/03 Teste Funcional\n02/03 Reces\xffso\r\n15 24/03 Prova 1";
+
+        let lines: Vec<_> = read_lines(contents)
+            .map(|line| {
+                let line = line.unwrap();
+                (line.number, line.offset, line.text)
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                (1, 0, "9 03/03 Teste Funcional".to_string()),
+                (2, 24, "02/03 Reces\u{fffd}so".to_string()),
+                (3, 40, "15 24/03 Prova 1".to_string())
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn should_report_byte_positions_after_invalid_utf8() {
+        let contents: &[u8] = b"ab\xffcd Recesso\n\xe2\x82Recesso\xff\xfe Recesso";
+        let mut lines: Vec<SearchLine> = Vec::new();
+
+        searcher(Config {
+            query: "Recesso".to_string(),
+            ..Default::default()
+        })
+        .search_reader(Path::new("-"), contents, &mut lines)
+        .unwrap();
+
+        let positions: Vec<Vec<_>> = lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| (line.byte_position(span.start), line.byte_position(span.end)))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(vec![vec![(6, 13)], vec![(2, 9), (12, 19)]], positions);
+        assert_eq!("\u{fffd}Recesso\u{fffd}\u{fffd} Recesso", lines[1].text);
+    }
+}
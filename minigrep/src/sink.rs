@@ -0,0 +1,231 @@
+//! Destino dos resultados da busca: a saída em texto, em JSON Lines ou a memória
+
+use std::io::{self, Write};
+use std::ops::{AddAssign, Range};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::json::JsonPrinter;
+use crate::printer::Printer;
+use crate::Config;
+
+/// Uma linha lida durante a busca
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchLine {
+    /// Número da linha, a partir de 1
+    pub number: usize,
+    /// Posição, em bytes, do início da linha no arquivo
+    pub offset: usize,
+    pub text: String,
+    /// Intervalos, em bytes dentro de `text`, de cada ocorrência da query
+    pub spans: Vec<Range<usize>>,
+    /// Cada `�` de `text` que substitui bytes que não são UTF-8 válido: a sua posição em
+    /// `text` e quantos bytes do arquivo ele substitui
+    pub invalid: Vec<(usize, usize)>,
+}
+
+impl SearchLine {
+    /// Converte uma posição em `text` na posição, em bytes, a partir do início da linha no
+    /// arquivo, que é diferente quando a linha tinha bytes que não são UTF-8 válido.
+    pub fn byte_position(&self, position: usize) -> usize {
+        self.invalid
+            .iter()
+            .take_while(|(at, _)| *at < position)
+            .fold(position, |original, (_, bytes)| {
+                original + bytes - char::REPLACEMENT_CHARACTER.len_utf8()
+            })
+    }
+}
+
+/// Contadores de uma busca, de um arquivo ou de todos eles
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    /// Arquivos buscados
+    pub searches: usize,
+    /// Arquivos com pelo menos uma linha encontrada
+    pub searches_with_match: usize,
+    pub matched_lines: usize,
+    /// Ocorrências da query; uma linha pode ter várias
+    pub matches: usize,
+}
+
+impl AddAssign for Stats {
+    fn add_assign(&mut self, other: Self) {
+        self.searches += other.searches;
+        self.searches_with_match += other.searches_with_match;
+        self.matched_lines += other.matched_lines;
+        self.matches += other.matches;
+    }
+}
+
+/// Recebe os resultados do [`Searcher`](crate::Searcher), arquivo por arquivo.
+///
+/// Só [`Sink::matched`] é obrigatório. Os métodos que recebem linhas retornam se a busca no
+/// arquivo deve continuar, então um [`Sink`] pode parar depois da primeira ocorrência.
+pub trait Sink {
+    /// Chamado antes de qualquer resultado de `path`
+    fn begin(&mut self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Uma linha encontrada (ou, com `invert_match`, uma linha que não casa)
+    fn matched(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool>;
+
+    /// Uma linha de contexto, antes ou depois de uma linha encontrada
+    fn context(&mut self, _path: &Path, _line: &SearchLine) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    /// Chamado entre dois grupos de linhas não contíguas, quando há linhas de contexto
+    fn context_break(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Chamado depois do último resultado de `path`, com as estatísticas do arquivo
+    fn end(&mut self, _path: &Path, _stats: &Stats) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Chamado uma vez, depois de todos os arquivos, com as estatísticas somadas
+    fn summary(&mut self, _stats: &Stats, _elapsed: Duration) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Guarda as linhas encontradas em memória, sem o caminho dos arquivos.
+impl Sink for Vec<SearchLine> {
+    fn matched(&mut self, _path: &Path, line: &SearchLine) -> io::Result<bool> {
+        self.push(line.clone());
+        Ok(true)
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    fn begin(&mut self, path: &Path) -> io::Result<()> {
+        (**self).begin(path)
+    }
+
+    fn matched(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        (**self).matched(path, line)
+    }
+
+    fn context(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        (**self).context(path, line)
+    }
+
+    fn context_break(&mut self) -> io::Result<()> {
+        (**self).context_break()
+    }
+
+    fn end(&mut self, path: &Path, stats: &Stats) -> io::Result<()> {
+        (**self).end(path, stats)
+    }
+
+    fn summary(&mut self, stats: &Stats, elapsed: Duration) -> io::Result<()> {
+        (**self).summary(stats, elapsed)
+    }
+}
+
+/// Um [`Sink`] que imprime os resultados, como o texto e o JSON Lines do `run`
+pub(crate) trait Output: Sink {
+    /// Separa a saída de dois arquivos quando há linhas de contexto
+    fn separator(&mut self) -> io::Result<()>;
+
+    /// Copia a saída de outro [`Output`] do mesmo tipo, já formatada.
+    fn write_raw(&mut self, output: &[u8]) -> io::Result<()>;
+
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// Cria o [`Output`] escolhido pelo [`Config`]: JSON Lines com `--json` ou texto.
+///
+/// `show_path` prefixa as linhas de texto com o caminho do arquivo.
+pub(crate) fn new_output<'a, W: Write + 'a>(
+    out: W,
+    config: &Config,
+    color: bool,
+    show_path: bool,
+) -> Box<dyn Output + 'a> {
+    if config.json {
+        Box::new(JsonPrinter::new(out))
+    } else {
+        Box::new(Printer::new(out, config, color, show_path))
+    }
+}
+
+/// Entrega os resultados de vários arquivos, um depois do outro, direto a um [`Output`],
+/// com o separador antes da primeira linha de cada arquivo depois do primeiro.
+pub(crate) struct Streaming<'a> {
+    output: &'a mut dyn Output,
+    /// Se os arquivos são separados, como com linhas de contexto
+    separate: bool,
+    /// Descarrega a saída a cada linha, para quem acompanha a busca enquanto ela acontece
+    line_buffered: bool,
+    printed: bool,
+    file_printed: bool,
+}
+
+impl<'a> Streaming<'a> {
+    pub(crate) fn new(output: &'a mut dyn Output, separate: bool, line_buffered: bool) -> Self {
+        Self {
+            output,
+            separate,
+            line_buffered,
+            printed: false,
+            file_printed: false,
+        }
+    }
+
+    fn before_line(&mut self) -> io::Result<()> {
+        if !self.file_printed {
+            if self.printed && self.separate {
+                self.output.separator()?;
+            }
+            self.printed = true;
+            self.file_printed = true;
+        }
+        Ok(())
+    }
+
+    fn after_line(&mut self) -> io::Result<()> {
+        if self.line_buffered {
+            self.output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Sink for Streaming<'_> {
+    fn begin(&mut self, path: &Path) -> io::Result<()> {
+        self.file_printed = false;
+        self.output.begin(path)
+    }
+
+    fn matched(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        self.before_line()?;
+        let more = self.output.matched(path, line)?;
+        self.after_line()?;
+        Ok(more)
+    }
+
+    fn context(&mut self, path: &Path, line: &SearchLine) -> io::Result<bool> {
+        self.before_line()?;
+        let more = self.output.context(path, line)?;
+        self.after_line()?;
+        Ok(more)
+    }
+
+    fn context_break(&mut self) -> io::Result<()> {
+        self.output.context_break()
+    }
+
+    fn end(&mut self, path: &Path, stats: &Stats) -> io::Result<()> {
+        self.output.end(path, stats)
+    }
+
+    fn summary(&mut self, stats: &Stats, elapsed: Duration) -> io::Result<()> {
+        self.output.summary(stats, elapsed)
+    }
+}
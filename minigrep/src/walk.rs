@@ -0,0 +1,232 @@
+//! Percorre diretórios recursivamente, aplicando os filtros `--include` e `--exclude`,
+//! as regras de `.gitignore` e `.ignore` e pulando arquivos ocultos
+
+use std::error::Error;
+use std::path::{self, Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::ignore::IgnoreStack;
+
+/// Quantidade de bytes inspecionados para decidir se um arquivo é binário
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Filtro de arquivos construído a partir das globs de inclusão e exclusão.
+///
+/// Um arquivo é aceito quando casa com alguma glob de `include` (ou quando não há
+/// nenhuma) e não casa com nenhuma de `exclude`. As globs são testadas contra o
+/// caminho relativo à raiz e contra o nome do arquivo.
+pub struct FileFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl FileFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, globset::Error> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(build_glob_set(include)?)
+        };
+
+        Ok(Self {
+            include,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    fn is_excluded(&self, relative: &Path) -> bool {
+        matches(&self.exclude, relative)
+    }
+
+    fn is_included(&self, relative: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|include| matches(include, relative))
+    }
+}
+
+/// Como [`files`] trata arquivos ocultos e as regras de `.gitignore` e `.ignore`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WalkOptions {
+    /// Percorre também os arquivos e diretórios cujo nome começa com `.` (`--hidden`)
+    pub hidden: bool,
+    /// Não respeita `.gitignore` e `.ignore` (`--no-ignore`)
+    pub no_ignore: bool,
+}
+
+fn matches(set: &GlobSet, relative: &Path) -> bool {
+    set.is_match(relative) || relative.file_name().is_some_and(|name| set.is_match(name))
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build()
+}
+
+/// Retorna, em ordem alfabética, todos os arquivos sob `root` aceitos por `filter`.
+///
+/// Diretórios excluídos, ignorados ou ocultos não são visitados. Quando as regras de
+/// `.gitignore` valem, os diretórios `.git` também são pulados, mesmo com `hidden`.
+pub fn files(
+    root: &Path,
+    filter: &FileFilter,
+    options: WalkOptions,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let absolute = path::absolute(root)?;
+    let mut ignores = (!options.no_ignore).then(|| IgnoreStack::new(&absolute));
+
+    let walker = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                if let Some(ignores) = &mut ignores {
+                    ignores.push(&absolute);
+                }
+                return true;
+            }
+
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            if filter.is_excluded(relative) || (!options.hidden && is_hidden(entry)) {
+                return false;
+            }
+            let Some(ignores) = &mut ignores else {
+                return true;
+            };
+
+            let path = absolute.join(relative);
+            let is_dir = entry.file_type().is_dir();
+            ignores.truncate(entry.depth());
+            if ignores.is_ignored(&path, is_dir) || (is_dir && entry.file_name() == ".git") {
+                return false;
+            }
+            if is_dir {
+                ignores.push(&path);
+            }
+            true
+        });
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        if entry.file_type().is_file() && filter.is_included(relative) {
+            files.push(entry.into_path());
+        }
+    }
+
+    Ok(files)
+}
+
+fn is_hidden(entry: &DirEntry) -> bool {
+    entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// Heurística do `grep`: um arquivo com byte nulo no início é considerado binário.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    fn setup(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(format!("minigrep_walk_{name}"));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("notes.txt"), "feriado").unwrap();
+        fs::write(root.join("src/lib.rs"), "feriado").unwrap();
+        fs::write(root.join("src/nested/mod.rs"), "feriado").unwrap();
+        fs::write(root.join("target/out.rs"), "feriado").unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join(".env"), "feriado").unwrap();
+        root
+    }
+
+    fn relative(root: &Path, files: Vec<PathBuf>) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn should_walk_recursively() {
+        let root = setup("recursive");
+        let filter = FileFilter::new(&[], &[]).unwrap();
+        let options = WalkOptions {
+            hidden: true,
+            no_ignore: true,
+        };
+
+        assert_eq!(
+            vec![
+                ".env",
+                ".gitignore",
+                "notes.txt",
+                "src/lib.rs",
+                "src/nested/mod.rs",
+                "target/out.rs"
+            ],
+            relative(&root, files(&root, &filter, options).unwrap())
+        );
+    }
+
+    #[test]
+    fn should_apply_include_and_exclude() {
+        let root = setup("globs");
+        let filter = FileFilter::new(&["*.rs".to_string()], &["target".to_string()]).unwrap();
+
+        assert_eq!(
+            vec!["src/lib.rs", "src/nested/mod.rs"],
+            relative(
+                &root,
+                files(&root, &filter, WalkOptions::default()).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn should_skip_ignored_and_hidden_files() {
+        let root = setup("ignore");
+        let filter = FileFilter::new(&[], &[]).unwrap();
+
+        assert_eq!(
+            vec!["notes.txt", "src/lib.rs", "src/nested/mod.rs"],
+            relative(
+                &root,
+                files(&root, &filter, WalkOptions::default()).unwrap()
+            )
+        );
+
+        fs::write(root.join("src/.ignore"), "nested\n").unwrap();
+        let options = WalkOptions {
+            hidden: true,
+            no_ignore: false,
+        };
+        assert_eq!(
+            vec![
+                ".env",
+                ".gitignore",
+                "notes.txt",
+                "src/.ignore",
+                "src/lib.rs"
+            ],
+            relative(&root, files(&root, &filter, options).unwrap())
+        );
+    }
+
+    #[test]
+    fn should_detect_binary() {
+        assert!(is_binary(b"ELF\0\x01\x02"));
+        assert!(!is_binary("Apresentação TP 1".as_bytes()));
+    }
+}
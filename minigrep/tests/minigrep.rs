@@ -1,11 +1,16 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
 use minigrep::*;
 
 #[test]
 fn should_run_correctly() {
     let config = Config {
         case_sensitive: true,
-        filename: "cronograma.txt".to_string(),
+        filenames: vec!["../cronograma.txt".to_string()],
         query: "feriado".to_string(),
+        ..Default::default()
     };
 
     let result = minigrep::run(config).is_ok();
@@ -17,11 +22,339 @@ fn should_run_correctly() {
 fn should_occur_error() {
     let config = Config {
         case_sensitive: true,
-        filename: "cronograma.csv".to_string(),
+        filenames: vec!["cronograma.csv".to_string()],
         query: "feriado".to_string(),
+        ..Default::default()
     };
 
     let result = minigrep::run(config).is_err();
 
     assert!(result);
 }
+
+#[test]
+fn should_run_regex() {
+    let config = Config {
+        case_sensitive: true,
+        filenames: vec!["../cronograma.txt".to_string()],
+        query: r"^\d+ \d{2}/03".to_string(),
+        regex: true,
+        ..Default::default()
+    };
+
+    assert!(minigrep::run(config).is_ok());
+}
+
+#[test]
+fn should_occur_error_invalid_regex() {
+    let config = Config {
+        case_sensitive: true,
+        filenames: vec!["../cronograma.txt".to_string()],
+        query: "(feriado".to_string(),
+        regex: true,
+        ..Default::default()
+    };
+
+    assert!(minigrep::run(config).is_err());
+}
+
+#[test]
+fn should_run_recursively() {
+    let config = Config {
+        case_sensitive: true,
+        filenames: vec!["src".to_string()],
+        query: "fn search".to_string(),
+        include: vec!["*.rs".to_string()],
+        exclude: vec!["walk.rs".to_string()],
+        ..Default::default()
+    };
+
+    assert!(minigrep::run(config).is_ok());
+}
+
+#[test]
+fn should_occur_error_invalid_glob() {
+    let config = Config {
+        case_sensitive: true,
+        filenames: vec!["src".to_string()],
+        query: "fn search".to_string(),
+        include: vec!["[".to_string()],
+        ..Default::default()
+    };
+
+    assert!(minigrep::run(config).is_err());
+}
+
+#[test]
+fn should_parse_flags() {
+    let config = Config::new(["minigrep", "-vnw", "-s", "Prova", "../cronograma.txt"])
+        .expect("should parse the flags");
+
+    assert!(config.invert_match && config.line_number && config.word);
+    assert!(config.case_sensitive);
+    assert_eq!("Prova", config.query);
+    assert!(minigrep::run(config).is_ok());
+}
+
+#[test]
+fn should_occur_error_missing_query() {
+    let result = Config::new(["minigrep"]);
+
+    assert!(matches!(result, Err(ConfigError::MissingArgument(_))));
+}
+
+#[test]
+fn should_default_to_stdin() {
+    let config = Config::new(["minigrep", "Prova"]).expect("the filename is optional");
+
+    assert_eq!(vec![minigrep::STDIN_FILENAME], config.filenames);
+}
+
+#[test]
+fn should_search_stdin_in_a_pipeline() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args(["-n", "Recesso", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("should spawn minigrep");
+
+    let contents = fs::read("../cronograma.txt").unwrap();
+    child.stdin.take().unwrap().write_all(&contents).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "10:02/03 Recesso\n23:14/04 Recesso\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn should_run_with_context() {
+    let config = Config::new(["minigrep", "-n", "-C", "1", "Recesso", "../cronograma.txt"])
+        .expect("should parse the context flags");
+
+    assert_eq!(1, config.before_context);
+    assert_eq!(1, config.after_context);
+    assert!(minigrep::run(config).is_ok());
+}
+
+#[test]
+fn should_search_many_files_in_order() {
+    let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args([
+            "-j",
+            "4",
+            "-c",
+            "fn ",
+            "src/walk.rs",
+            "src/lib.rs",
+            "src/arg.rs",
+        ])
+        .output()
+        .expect("should run minigrep");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let files: Vec<_> = stdout
+        .lines()
+        .map(|line| line.split(':').next().unwrap())
+        .collect();
+    assert_eq!(vec!["src/walk.rs", "src/lib.rs", "src/arg.rs"], files);
+}
+
+#[test]
+fn should_print_json_lines() {
+    let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args(["--json", "Recesso", "../cronograma.txt"])
+        .output()
+        .expect("should run minigrep");
+
+    assert!(output.status.success());
+    let records: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be a JSON object"))
+        .collect();
+    let types: Vec<_> = records
+        .iter()
+        .map(|record| record["type"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(vec!["begin", "match", "match", "end", "summary"], types);
+    assert_eq!("../cronograma.txt", records[1]["data"]["path"]);
+    assert_eq!(10, records[1]["data"]["line_number"]);
+    assert_eq!("02/03 Recesso", records[1]["data"]["text"]);
+    assert_eq!(
+        serde_json::json!([{"match": "Recesso", "start": 6, "end": 13}]),
+        records[1]["data"]["submatches"]
+    );
+    assert_eq!(2, records[4]["data"]["stats"]["matched_lines"]);
+}
+
+#[test]
+fn should_search_many_patterns() {
+    let patterns = std::env::temp_dir().join(format!("minigrep-patterns-{}", std::process::id()));
+    fs::write(&patterns, "Feriado\nSeminário\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args(["-n", "-e", "Recesso", "-f"])
+        .arg(&patterns)
+        .arg("../cronograma.txt")
+        .output()
+        .expect("should run minigrep");
+    fs::remove_file(&patterns).unwrap();
+
+    assert!(output.status.success());
+    let numbers: Vec<_> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.split(':').next().unwrap().to_string())
+        .collect();
+    let expected: Vec<_> = fs::read_to_string("../cronograma.txt")
+        .unwrap()
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            ["Recesso", "Feriado", "Seminário"]
+                .iter()
+                .any(|p| line.contains(p))
+        })
+        .map(|(index, _)| (index + 1).to_string())
+        .collect();
+    assert!(expected.len() > 2);
+    assert_eq!(expected, numbers);
+}
+
+#[test]
+fn should_replace_in_place() {
+    let path = std::env::temp_dir().join(format!("minigrep-in-place-{}.txt", std::process::id()));
+    let contents = "02/03 Recesso\n9 03/03 Teste Funcional\n14/04 Recesso\n";
+    fs::write(&path, contents).unwrap();
+    let replace = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_minigrep"))
+            .args(["-E", "-r", "$2/$1 Feriado", "--in-place"])
+            .args(extra)
+            .args([r"(\d{2})/(\d{2}) Recesso"])
+            .arg(&path)
+            .output()
+            .expect("should run minigrep")
+    };
+
+    let output = replace(&["--dry-run"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("@@ -3 +3 @@\n-14/04 Recesso\n+04/14 Feriado\n"));
+    assert_eq!(contents, fs::read_to_string(&path).unwrap());
+
+    let output = replace(&[]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        "03/02 Feriado\n9 03/03 Teste Funcional\n04/14 Feriado\n",
+        fs::read_to_string(&path).unwrap()
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn should_search_with_typos() {
+    let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args([
+            "-n",
+            "-i",
+            "--fuzzy",
+            "1",
+            "--ignore-accents",
+            "apresentaçao",
+            "../cronograma.txt",
+        ])
+        .output()
+        .expect("should run minigrep");
+
+    assert!(output.status.success());
+    assert_eq!(
+        "2:1 02/02 Apresentação da Disciplina\n\
+         14:12 16/03 Apresentação TP 1\n\
+         15:13 17/03 Apresentação TP 1\n\
+         40:36 15/06 Apresentação do TP Final\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn should_search_compressed_files() {
+    let path = std::env::temp_dir().join(format!("minigrep-{}.log.1.gz", std::process::id()));
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(&fs::read("../cronograma.txt").unwrap())
+        .unwrap();
+    encoder.write_all(b"\n99 01/07 Reces\xffso\n").unwrap();
+    fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_minigrep"))
+        .args(["-z", "-n", "Reces"])
+        .arg(&path)
+        .output()
+        .expect("should run minigrep");
+    fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        "10:02/03 Recesso\n23:14/04 Recesso\n49:99 01/07 Reces\u{fffd}so\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
+
+#[test]
+fn should_embed_searcher_with_custom_sink() {
+    /// Guarda só o número das linhas encontradas e o total de arquivos
+    #[derive(Default)]
+    struct Numbers {
+        lines: Vec<usize>,
+        files: usize,
+    }
+
+    impl Sink for Numbers {
+        fn matched(&mut self, _path: &std::path::Path, line: &SearchLine) -> std::io::Result<bool> {
+            self.lines.push(line.number);
+            Ok(true)
+        }
+
+        fn end(&mut self, _path: &std::path::Path, _stats: &Stats) -> std::io::Result<()> {
+            self.files += 1;
+            Ok(())
+        }
+    }
+
+    let config = Config {
+        query: "Feriado".to_string(),
+        case_sensitive: true,
+        ..Default::default()
+    };
+    let searcher = Searcher::from_config(&config).unwrap();
+    let mut numbers = Numbers::default();
+
+    let stats = searcher
+        .search_path("../cronograma.txt".as_ref(), &mut numbers)
+        .unwrap();
+
+    assert_eq!(vec![25, 41], numbers.lines);
+    assert_eq!(1, numbers.files);
+    assert_eq!(2, stats.matched_lines);
+
+    let mut lines: Vec<SearchLine> = Vec::new();
+    searcher
+        .search_reader(
+            "-".as_ref(),
+            "02/03 Recesso\n21/04 Feriado".as_bytes(),
+            &mut lines,
+        )
+        .unwrap();
+    assert_eq!(
+        vec!["21/04 Feriado"],
+        lines.iter().map(|line| &line.text).collect::<Vec<_>>()
+    );
+}
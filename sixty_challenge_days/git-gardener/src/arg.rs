@@ -5,11 +5,10 @@ use std::{
 };
 
 use clap::{
-    arg,
     builder::{OsStringValueParser, PossibleValue, TypedValueParser},
     Parser, Subcommand,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Parser, Debug)]
 #[command(name = "Git Gardener")]
@@ -26,13 +25,45 @@ pub struct GitGardenerArgs {
         short,
         long,
         help = "The principal branch of repository",
-        default_value = "main"
+        default_value = DEFAULT_MAIN_BRANCH
     )]
     pub main_branch: String,
 
     #[arg(short, long, help = "Show what would be deleted")]
     pub dry_run: bool,
 
+    #[arg(
+        short,
+        long,
+        help = "Choose which of the branches to delete, and confirm, before deleting them"
+    )]
+    pub interactive: bool,
+
+    #[arg(
+        short = 'P',
+        long,
+        value_name = "GLOB",
+        help = "Never delete branches matching this pattern, like 'release/*' (repeatable)"
+    )]
+    pub protect: Vec<String>,
+
+    #[arg(
+        short,
+        long,
+        value_name = "REMOTE",
+        help = "Clean up the branches of this remote instead of the local ones, deleting them with a push"
+    )]
+    pub remote: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "How to print the results",
+        value_enum,
+        default_value_t
+    )]
+    pub format: OutputFormat,
+
     #[arg(short, long, env, default_value = ".", value_parser = OsStringValueParser::new().try_map(parse_path) , help = "Path of the Git Repository to use")]
     pub git_repository: PathBuf,
 
@@ -40,7 +71,20 @@ pub struct GitGardenerArgs {
     pub command: Option<StalenessDate>,
 }
 
-#[derive(clap::ValueEnum, Default, Clone, Debug, Serialize, PartialEq, Eq)]
+/// Main branch when neither the command line nor the configuration sets one
+pub const DEFAULT_MAIN_BRANCH: &str = "main";
+/// Staleness number when neither the command line nor the configuration sets one
+pub const DEFAULT_STALENESS_NUMBER: u64 = 3;
+
+#[derive(clap::ValueEnum, Default, Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Default, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum PeriodArgs {
     Days,
@@ -57,8 +101,8 @@ pub enum StalenessDate {
         #[arg(
             short,
             long,
-            required = true,
-            help = r"Number of PERIOD to use as a reference"
+            help = r"Number of PERIOD to use as a reference",
+            default_value_t = DEFAULT_STALENESS_NUMBER
         )]
         number: u64,
 
@@ -71,6 +115,28 @@ pub enum StalenessDate {
         )]
         period: PeriodArgs,
     },
+    /// Delete the branches already merged into the main branch.
+    ///
+    /// A branch is merged when its last commit is part of the main branch history, or when
+    /// its changes were squash merged into the main branch.
+    Merged,
+    /// Delete the local branches whose upstream branch is gone from its remote.
+    ///
+    /// Run `git fetch --prune` first, so the remote-tracking branches are up to date. With
+    /// `--remote`, only the branches tracking that remote are considered.
+    Gone,
+    /// Inspect the configuration read from `.gitgardener.toml` and the `gitgardener.*` git
+    /// config keys.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective configuration and where each value comes from
+    Show,
 }
 
 /// Helper function to parse and return the absolute path
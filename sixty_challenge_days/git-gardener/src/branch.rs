@@ -1,12 +1,54 @@
 use anyhow::Result;
-use chrono::{DateTime, FixedOffset, Local, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, Local};
 use chrono::{Days, Months};
 use colored::Colorize;
-use git2::Time;
+use git2::{Repository, Time};
+use serde_json::json;
 
-use crate::arg::{GitGardenerArgs, PeriodArgs, StalenessDate};
+use crate::arg::{GitGardenerArgs, OutputFormat, PeriodArgs, StalenessDate};
+use crate::candidate::{find_candidates, Candidate};
+use crate::protection::Protected;
 use crate::GitGardener;
 
+/// What a cleanup deleted (or would delete, in a dry run) and what it kept on purpose
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub deleted: Vec<String>,
+    /// Branches that matched the cleanup criteria but are protected
+    pub skipped: Vec<(String, Protected)>,
+}
+
+impl Report {
+    pub fn print(&self, dry_run: bool, format: OutputFormat) {
+        match format {
+            OutputFormat::Text => self.print_text(dry_run),
+            OutputFormat::Json => println!("{}", self.to_json(dry_run)),
+        }
+    }
+
+    fn print_text(&self, dry_run: bool) {
+        for name in &self.deleted {
+            if dry_run {
+                println!("This branch will be deleted {}", name.red());
+            } else {
+                println!("Deleted branch {}", name.red());
+            }
+        }
+        for (name, reason) in &self.skipped {
+            println!("Skipped {} because {}", name.yellow(), reason);
+        }
+    }
+
+    fn to_json(&self, dry_run: bool) -> serde_json::Value {
+        let skipped: Vec<_> = self
+            .skipped
+            .iter()
+            .map(|(name, reason)| json!({ "branch": name, "reason": reason.to_string() }))
+            .collect();
+        json!({ "dry_run": dry_run, "deleted": self.deleted, "skipped": skipped })
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct Branch {
     pub number: u64,
@@ -15,19 +57,21 @@ pub struct Branch {
 
 impl Branch {
     pub fn load_from_args(args: &Option<StalenessDate>) -> Option<Self> {
-        args.as_ref().and_then(|steleness| match steleness {
+        args.as_ref().and_then(|command| match command {
             StalenessDate::Steleness { number, period } => Some(Self {
                 number: *number,
                 period: period.clone(),
             }),
+            StalenessDate::Merged | StalenessDate::Gone | StalenessDate::Config { .. } => None,
         })
     }
 
-    pub fn delete_steleness_branches(
+    /// The branches whose last commit is older than the staleness period.
+    pub fn steleness_candidates(
         &self,
         args: &GitGardenerArgs,
         gardener: &GitGardener,
-    ) -> Result<()> {
+    ) -> Result<Vec<Candidate>> {
         let period_of = match self.period {
             PeriodArgs::Days => Local::now().checked_sub_days(Days::new(self.number)),
             PeriodArgs::Months => {
@@ -35,51 +79,41 @@ impl Branch {
             }
         };
 
-        let repository = gardener
-            .repository
-            .as_ref()
-            .expect("Repository has already be set");
-
-        let branches = repository.branches(Some(git2::BranchType::Local))?;
-        let branches = branches.filter_map(|branch| branch.ok());
-
-        for (mut branch, _) in branches {
-            if let Ok(commit) = branch.get().peel_to_commit() {
-                let time = self.get_commit_date(commit.time());
-                if period_of > time {
-                    if args.dry_run {
-                        if let Some(name) =
-                            branch.get().name().and_then(|name| name.split("/").last())
-                        {
-                            if name != args.main_branch {
-                                println!("This branch will be deleted {}", name.red());
-                            }
-                        }
-                    } else {
-                        branch.delete()?;
-                    }
-                }
-            }
-        }
-        Ok(())
+        find_candidates(args, repository(gardener), |branch| {
+            let commit = branch.get().peel_to_commit()?;
+            Ok(period_of > self.get_commit_date(commit.time()))
+        })
     }
 
     fn get_commit_date(&self, time: Time) -> Option<DateTime<Local>> {
-        let hour = 60;
-        FixedOffset::east_opt(time.offset_minutes() * hour).map(|offset| {
-            let datetime = NaiveDateTime::from_timestamp_opt(time.seconds(), 0)?;
-            let datetime = DateTime::<Local>::from_naive_utc_and_offset(datetime, offset);
-
-            Some(datetime)
-        })?
+        commit_date(time)
     }
 }
 
+pub fn repository(gardener: &GitGardener) -> &Repository {
+    gardener
+        .repository
+        .as_ref()
+        .expect("Repository has already be set")
+}
+
+/// Date of a commit `time`, in the time zone of the commit
+pub fn commit_date(time: Time) -> Option<DateTime<Local>> {
+    let hour = 60;
+    FixedOffset::east_opt(time.offset_minutes() * hour).map(|offset| {
+        let datetime = DateTime::from_timestamp(time.seconds(), 0)?.naive_utc();
+        let datetime = DateTime::<Local>::from_naive_utc_and_offset(datetime, offset);
+
+        Some(datetime)
+    })?
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use git2::Repository;
+    use crate::candidate::delete_candidates;
+    use crate::testing::TestRepo;
     use std::path::PathBuf;
 
     #[test]
@@ -118,24 +152,106 @@ mod tests {
 
         let args = GitGardenerArgs {
             dry_run: true,
+            interactive: false,
             main_branch: "master".to_string(),
+            protect: Vec::new(),
+            remote: None,
+            format: OutputFormat::Text,
             git_repository: repo_path.clone(),
             command: None,
         };
-        branch.delete_steleness_branches(&args, &gardener)?;
+        let candidates = branch.steleness_candidates(&args, &gardener)?;
+        delete_candidates(&args, repository(&gardener), candidates)?;
 
         let args = GitGardenerArgs {
             dry_run: false,
+            interactive: false,
             main_branch: "master".to_string(),
+            protect: Vec::new(),
+            remote: None,
+            format: OutputFormat::Text,
             git_repository: repo_path,
             command: None,
         };
 
-        branch.delete_steleness_branches(&args, &gardener)?;
+        let candidates = branch.steleness_candidates(&args, &gardener)?;
+        delete_candidates(&args, repository(&gardener), candidates)?;
 
         Ok(())
     }
 
+    #[test]
+    fn test_keep_protected_branches() -> Result<()> {
+        let repo = TestRepo::new()?;
+        for name in ["develop", "release/1.0", "feature/old", "feature/current"] {
+            repo.branch(name, 300)?;
+        }
+        repo.branch("feature/new", 2)?;
+        repo.repository.set_head("refs/heads/feature/current")?;
+        let branch = Branch {
+            number: 7,
+            period: PeriodArgs::Months,
+        };
+
+        for dry_run in [true, false] {
+            let mut args = repo.args(&["--protect", "release/*", "-P", "develop"]);
+            args.dry_run = dry_run;
+            let gardener = GitGardener {
+                repository: Some(Repository::open(repo.dir.path())?),
+                branch: None,
+            };
+
+            let candidates = branch.steleness_candidates(&args, &gardener)?;
+            let report = delete_candidates(&args, repository(&gardener), candidates)?;
+
+            assert_eq!(report.deleted, vec!["feature/old"]);
+            assert_eq!(
+                report.skipped,
+                vec![
+                    (
+                        "develop".to_string(),
+                        Protected::Pattern("develop".to_string())
+                    ),
+                    ("feature/current".to_string(), Protected::Head),
+                    ("main".to_string(), Protected::MainBranch),
+                    (
+                        "release/1.0".to_string(),
+                        Protected::Pattern("release/*".to_string())
+                    ),
+                ]
+            );
+        }
+        assert_eq!(
+            repo.branches()?,
+            vec![
+                "develop",
+                "feature/current",
+                "feature/new",
+                "main",
+                "release/1.0"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_to_json() {
+        let report = Report {
+            deleted: vec!["feature/old".to_string()],
+            skipped: vec![("main".to_string(), Protected::MainBranch)],
+        };
+
+        assert_eq!(
+            report.to_json(true),
+            json!({
+                "dry_run": true,
+                "deleted": ["feature/old"],
+                "skipped": [{ "branch": "main", "reason": "it is the main branch" }]
+            })
+        );
+    }
+
     #[test]
     fn test_get_commit_date() {
         let branch = Branch {
@@ -146,6 +262,6 @@ mod tests {
         let time = Time::new(1627639200, 0);
 
         let commit_date = branch.get_commit_date(time);
-        assert_eq!(commit_date.is_some(), true);
+        assert!(commit_date.is_some());
     }
 }
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use git2::{BranchType, Commit, Repository};
+
+use crate::arg::GitGardenerArgs;
+use crate::branch::{commit_date, Report};
+use crate::merged::{main_commit, MergeCheck, MergeStatus};
+use crate::protection::{Protected, Protection};
+use crate::remote;
+
+/// A branch that matches the cleanup criteria, with what is needed to decide whether to
+/// delete it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// Short name, like `feature/x` or `origin/feature/x` for a remote branch
+    pub name: String,
+    /// The remote of a remote-tracking branch
+    pub remote: Option<String>,
+    pub last_commit: Option<DateTime<Local>>,
+    pub author: String,
+    /// Commits only on the branch and commits only on the main branch, filled in by
+    /// [`describe_candidates`] when the main branch exists
+    pub ahead_behind: Option<(usize, usize)>,
+    /// Filled in by [`describe_candidates`], or already by the `merged` command
+    pub merge_status: Option<MergeStatus>,
+    /// Protected candidates are reported but never deleted
+    pub protected: Option<Protected>,
+}
+
+impl Candidate {
+    fn new(name: String, remote: Option<&str>, tip: &Commit, protected: Option<Protected>) -> Self {
+        Self {
+            name,
+            remote: remote.map(str::to_string),
+            last_commit: commit_date(tip.time()),
+            author: tip.author().name().unwrap_or_default().to_string(),
+            ahead_behind: None,
+            merge_status: None,
+            protected,
+        }
+    }
+
+    /// The name of the branch on its remote, or the local name
+    pub fn branch_name(&self) -> &str {
+        self.remote
+            .as_ref()
+            .and_then(|remote| self.name.strip_prefix(remote.as_str()))
+            .and_then(|name| name.strip_prefix('/'))
+            .unwrap_or(&self.name)
+    }
+}
+
+/// The branches for which `is_candidate` is true: the branches of the `--remote` when
+/// given, the local ones otherwise.
+pub fn find_candidates<F>(
+    args: &GitGardenerArgs,
+    repository: &Repository,
+    is_candidate: F,
+) -> Result<Vec<Candidate>>
+where
+    F: FnMut(&git2::Branch) -> Result<bool>,
+{
+    find(args, repository, args.remote.as_deref(), is_candidate)
+}
+
+/// The local branches for which `is_candidate` is true, even with `--remote`.
+pub fn find_local_candidates<F>(
+    args: &GitGardenerArgs,
+    repository: &Repository,
+    is_candidate: F,
+) -> Result<Vec<Candidate>>
+where
+    F: FnMut(&git2::Branch) -> Result<bool>,
+{
+    find(args, repository, None, is_candidate)
+}
+
+/// Branches whose tip cannot be read are left out, as is `origin/HEAD`, which only points
+/// to another branch of the remote.
+fn find<F>(
+    args: &GitGardenerArgs,
+    repository: &Repository,
+    remote: Option<&str>,
+    mut is_candidate: F,
+) -> Result<Vec<Candidate>>
+where
+    F: FnMut(&git2::Branch) -> Result<bool>,
+{
+    if let Some(remote) = remote {
+        check_remote(repository, remote)?;
+    }
+    let protection = Protection::load(args, repository)?;
+    let branch_type = match remote {
+        Some(_) => BranchType::Remote,
+        None => BranchType::Local,
+    };
+    let mut candidates = Vec::new();
+
+    let branches = repository.branches(Some(branch_type))?;
+    let branches = branches.filter_map(|branch| branch.ok());
+
+    for (branch, _) in branches {
+        let Some(name) = branch.name()?.map(str::to_string) else {
+            continue;
+        };
+        let protected = match remote {
+            Some(remote) => match name.strip_prefix(&format!("{remote}/")) {
+                Some(short_name) => protection.check_name(short_name),
+                None => continue,
+            },
+            None => protection.check(&branch),
+        };
+        let Ok(tip) = branch.get().peel_to_commit() else {
+            continue;
+        };
+        if branch.get().symbolic_target().is_some() || !is_candidate(&branch)? {
+            continue;
+        }
+
+        candidates.push(Candidate::new(name, remote, &tip, protected));
+    }
+
+    Ok(candidates)
+}
+
+/// Fills in how the `candidates` compare to the main branch, which walks their history, so
+/// only for showing them to the user. Protected candidates, those whose main branch is
+/// missing and merge statuses already known are left as they are.
+pub fn describe_candidates(
+    args: &GitGardenerArgs,
+    repository: &Repository,
+    candidates: &mut [Candidate],
+) -> Result<()> {
+    // One per remote, as the candidates of `gone` are local even with `--remote`
+    let mut checks: BTreeMap<Option<String>, Option<MergeCheck>> = BTreeMap::new();
+
+    for candidate in candidates {
+        if candidate.protected.is_some() {
+            continue;
+        }
+        let remote = candidate.remote.as_deref();
+        let check = checks.entry(candidate.remote.clone()).or_insert_with(|| {
+            main_commit(repository, &args.main_branch, remote)
+                .ok()
+                .map(|main| MergeCheck::new(repository, main))
+        });
+        let Some(check) = check else {
+            continue;
+        };
+        let branch_type = match remote {
+            Some(_) => BranchType::Remote,
+            None => BranchType::Local,
+        };
+        let tip = repository
+            .find_branch(&candidate.name, branch_type)?
+            .get()
+            .peel_to_commit()?;
+
+        candidate.ahead_behind = Some(repository.graph_ahead_behind(tip.id(), check.main_id())?);
+        if candidate.merge_status.is_none() {
+            candidate.merge_status = Some(check.status(&tip)?);
+        }
+    }
+    Ok(())
+}
+
+/// Fails when `remote` is not a remote of the repository, so a typo is not taken for a
+/// remote without candidates.
+pub fn check_remote(repository: &Repository, remote: &str) -> Result<()> {
+    repository
+        .find_remote(remote)
+        .with_context(|| format!("remote `{remote}` not found"))?;
+    Ok(())
+}
+
+/// Deletes the `candidates` that are not protected; the branches of a remote with a push.
+/// With `--dry-run`, only reports them.
+pub fn delete_candidates(
+    args: &GitGardenerArgs,
+    repository: &Repository,
+    candidates: Vec<Candidate>,
+) -> Result<Report> {
+    let mut report = Report::default();
+    let mut remote_branches: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for candidate in candidates {
+        if let Some(reason) = candidate.protected {
+            report.skipped.push((candidate.name, reason));
+            continue;
+        }
+        if !args.dry_run {
+            match &candidate.remote {
+                Some(remote) => remote_branches
+                    .entry(remote.clone())
+                    .or_default()
+                    .push(candidate.branch_name().to_string()),
+                None => repository
+                    .find_branch(&candidate.name, BranchType::Local)?
+                    .delete()?,
+            }
+        }
+        report.deleted.push(candidate.name);
+    }
+
+    for (remote, branches) in remote_branches {
+        remote::delete_remote_branches(repository, &remote, &branches)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRepo;
+
+    #[test]
+    fn test_find_candidates() -> Result<()> {
+        let repo = TestRepo::new()?;
+        let merged = repo.branch("feature/merged", 20)?;
+        repo.repository
+            .reference("refs/heads/main", merged, true, "fast-forward")?;
+        repo.branch("feature/open", 10)?;
+        repo.commit("feature/open", "second", 9)?;
+        repo.commit("main", "on-main", 5)?;
+        let args = repo.args(&[]);
+
+        let mut candidates = find_candidates(&args, &repo.repository, |_| Ok(true))?;
+        assert!(candidates
+            .iter()
+            .all(|candidate| candidate.ahead_behind.is_none() && candidate.merge_status.is_none()));
+        describe_candidates(&args, &repo.repository, &mut candidates)?;
+
+        let summary: Vec<_> = candidates
+            .iter()
+            .map(|candidate| {
+                (
+                    candidate.name.as_str(),
+                    candidate.ahead_behind,
+                    candidate.merge_status,
+                    candidate.protected.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "feature/merged",
+                    Some((0, 1)),
+                    Some(MergeStatus::Merged),
+                    None
+                ),
+                (
+                    "feature/open",
+                    Some((2, 1)),
+                    Some(MergeStatus::NotMerged),
+                    None
+                ),
+                ("main", None, None, Some(Protected::Head)),
+            ]
+        );
+        assert_eq!(candidates[0].author, "Gardener");
+        assert!(candidates[1].last_commit > candidates[0].last_commit);
+        // Finding the candidates deletes nothing
+        assert_eq!(
+            repo.branches()?,
+            vec!["feature/merged", "feature/open", "main"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_selected_candidates() -> Result<()> {
+        let repo = TestRepo::new()?;
+        repo.branch("feature/a", 10)?;
+        repo.branch("feature/b", 10)?;
+        let args = repo.args(&[]);
+        let candidates = find_candidates(&args, &repo.repository, |_| Ok(true))?;
+
+        let selected = candidates
+            .into_iter()
+            .filter(|candidate| candidate.name != "feature/a")
+            .collect();
+        let report = delete_candidates(&args, &repo.repository, selected)?;
+
+        assert_eq!(report.deleted, vec!["feature/b"]);
+        assert_eq!(report.skipped, vec![("main".to_string(), Protected::Head)]);
+        assert_eq!(repo.branches()?, vec!["feature/a", "main"]);
+        Ok(())
+    }
+}
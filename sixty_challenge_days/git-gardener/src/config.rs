@@ -0,0 +1,454 @@
+use std::{
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::{parser::ValueSource, ArgMatches, ValueEnum};
+use git2::Repository;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::arg::{
+    GitGardenerArgs, OutputFormat, PeriodArgs, StalenessDate, DEFAULT_MAIN_BRANCH,
+    DEFAULT_STALENESS_NUMBER,
+};
+use crate::protection;
+
+/// Configuration file read from the root of the working tree
+pub const FILE_NAME: &str = ".gitgardener.toml";
+
+const MAIN_BRANCH_KEY: &str = "gitgardener.mainBranch";
+const STALENESS_NUMBER_KEY: &str = "gitgardener.stalenessNumber";
+const STALENESS_PERIOD_KEY: &str = "gitgardener.stalenessPeriod";
+const FORMAT_KEY: &str = "gitgardener.format";
+
+/// Where a configuration value comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    /// The git config key
+    GitConfig(&'static str),
+    CommandLine,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::GitConfig(key) => write!(f, "git config {key}"),
+            Source::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Setting<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Setting<T> {
+    fn new(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+
+    fn set(&mut self, value: Option<T>, source: &Source) {
+        if let Some(value) = value {
+            *self = Self::new(value, source.clone());
+        }
+    }
+}
+
+/// The settings of a run, from the lowest to the highest priority: the defaults,
+/// [`FILE_NAME`], the `gitgardener.*` git config keys and the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub main_branch: Setting<String>,
+    pub staleness_number: Setting<u64>,
+    pub staleness_period: Setting<PeriodArgs>,
+    pub format: Setting<OutputFormat>,
+    /// Protected patterns of every source, as all of them apply
+    pub protect: Vec<Setting<String>>,
+}
+
+/// The contents of [`FILE_NAME`]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    main_branch: Option<String>,
+    #[serde(default)]
+    protect: Vec<String>,
+    format: Option<OutputFormat>,
+    staleness: Option<FileStaleness>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileStaleness {
+    number: Option<u64>,
+    period: Option<PeriodArgs>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            main_branch: Setting::new(DEFAULT_MAIN_BRANCH.to_string(), Source::Default),
+            staleness_number: Setting::new(DEFAULT_STALENESS_NUMBER, Source::Default),
+            staleness_period: Setting::new(PeriodArgs::default(), Source::Default),
+            format: Setting::new(OutputFormat::default(), Source::Default),
+            protect: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads [`FILE_NAME`] from the working tree of `repository`, if there is one, and then
+    /// the git config of `repository`.
+    pub fn load(repository: &Repository) -> Result<Self> {
+        let mut config = Self::default();
+        if let Some(workdir) = repository.workdir() {
+            config.read_file(&workdir.join(FILE_NAME))?;
+        }
+        config.read_git_config(&repository.config()?)?;
+        Ok(config)
+    }
+
+    fn read_file(&mut self, path: &Path) -> Result<()> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("could not read {}", path.display()))
+            }
+        };
+        let file: File = toml::from_str(&contents)
+            .with_context(|| format!("invalid configuration file {}", path.display()))?;
+        let source = Source::File(path.to_path_buf());
+
+        self.main_branch.set(file.main_branch, &source);
+        self.format.set(file.format, &source);
+        let staleness = file.staleness.unwrap_or_default();
+        self.staleness_number.set(staleness.number, &source);
+        self.staleness_period.set(staleness.period, &source);
+        self.protect.extend(
+            file.protect
+                .into_iter()
+                .map(|pattern| Setting::new(pattern, source.clone())),
+        );
+        Ok(())
+    }
+
+    fn read_git_config(&mut self, config: &git2::Config) -> Result<()> {
+        self.main_branch.set(
+            git_config_string(config, MAIN_BRANCH_KEY)?,
+            &Source::GitConfig(MAIN_BRANCH_KEY),
+        );
+        if let Some(number) = git_config_string(config, STALENESS_NUMBER_KEY)? {
+            let number = number
+                .parse()
+                .with_context(|| format!("{STALENESS_NUMBER_KEY} must be a positive number"))?;
+            self.staleness_number = Setting::new(number, Source::GitConfig(STALENESS_NUMBER_KEY));
+        }
+        self.staleness_period.set(
+            git_config_enum(config, STALENESS_PERIOD_KEY)?,
+            &Source::GitConfig(STALENESS_PERIOD_KEY),
+        );
+        self.format.set(
+            git_config_enum(config, FORMAT_KEY)?,
+            &Source::GitConfig(FORMAT_KEY),
+        );
+
+        let mut entries = match config.multivar(protection::CONFIG_KEY, None) {
+            Ok(entries) => entries,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        while let Some(entry) = entries.next() {
+            if let Some(pattern) = entry?.value() {
+                self.protect.push(Setting::new(
+                    pattern.to_string(),
+                    Source::GitConfig(protection::CONFIG_KEY),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Overrides the configured values with the ones given on the command line.
+    ///
+    /// `matches` tell which of the `args` were given and which are only clap defaults.
+    pub fn with_args(mut self, args: &GitGardenerArgs, matches: &ArgMatches) -> Self {
+        if from_command_line(matches, "main_branch") {
+            self.main_branch = Setting::new(args.main_branch.clone(), Source::CommandLine);
+        }
+        if from_command_line(matches, "format") {
+            self.format = Setting::new(args.format, Source::CommandLine);
+        }
+        if let (Some(StalenessDate::Steleness { number, period }), Some((_, matches))) =
+            (&args.command, matches.subcommand())
+        {
+            if from_command_line(matches, "number") {
+                self.staleness_number = Setting::new(*number, Source::CommandLine);
+            }
+            if from_command_line(matches, "period") {
+                self.staleness_period = Setting::new(period.clone(), Source::CommandLine);
+            }
+        }
+
+        let mut protect: Vec<_> = args
+            .protect
+            .iter()
+            .map(|pattern| Setting::new(pattern.clone(), Source::CommandLine))
+            .collect();
+        protect.append(&mut self.protect);
+        self.protect = protect;
+        self
+    }
+
+    /// Puts the effective configuration in `args`.
+    pub fn apply(&self, args: &mut GitGardenerArgs) {
+        args.main_branch = self.main_branch.value.clone();
+        args.format = self.format.value;
+        if let Some(StalenessDate::Steleness { number, period }) = &mut args.command {
+            *number = self.staleness_number.value;
+            *period = self.staleness_period.value.clone();
+        }
+        // `Protection::load` reads the git config patterns itself
+        args.protect = self
+            .protect
+            .iter()
+            .filter(|pattern| !matches!(pattern.source, Source::GitConfig(_)))
+            .map(|pattern| pattern.value.clone())
+            .collect();
+    }
+
+    /// Every value with its key and source, in the order `config show` prints them
+    fn entries(&self) -> Vec<(&'static str, String, &Source)> {
+        let mut entries = vec![
+            (
+                "main_branch",
+                self.main_branch.value.clone(),
+                &self.main_branch.source,
+            ),
+            (
+                "staleness.number",
+                self.staleness_number.value.to_string(),
+                &self.staleness_number.source,
+            ),
+            (
+                "staleness.period",
+                value_name(&self.staleness_period.value),
+                &self.staleness_period.source,
+            ),
+            (
+                "format",
+                value_name(&self.format.value),
+                &self.format.source,
+            ),
+        ];
+        for pattern in &self.protect {
+            entries.push(("protect", pattern.value.clone(), &pattern.source));
+        }
+        entries
+    }
+
+    /// Prints the effective configuration and the source of each value.
+    pub fn show<W: Write>(&self, output: &mut W) -> Result<()> {
+        let entries = self.entries();
+        match self.format.value {
+            OutputFormat::Text => {
+                let width = entries
+                    .iter()
+                    .map(|(key, value, _)| key.len() + value.len())
+                    .max()
+                    .unwrap_or(0);
+                for (key, value, source) in entries {
+                    let padding = width - key.len() - value.len();
+                    writeln!(output, "{key} = {value}{:padding$}  ({source})", "")?;
+                }
+            }
+            OutputFormat::Json => {
+                let entries: Vec<_> = entries
+                    .into_iter()
+                    .map(|(key, value, source)| {
+                        json!({ "key": key, "value": value, "source": source.to_string() })
+                    })
+                    .collect();
+                serde_json::to_writer_pretty(&mut *output, &entries)?;
+                writeln!(output)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches!(
+        matches.value_source(id),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+    )
+}
+
+fn git_config_string(config: &git2::Config, key: &str) -> Result<Option<String>> {
+    match config.get_string(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("invalid value for {key}")),
+    }
+}
+
+fn git_config_enum<T: ValueEnum>(config: &git2::Config, key: &str) -> Result<Option<T>> {
+    git_config_string(config, key)?
+        .map(|value| {
+            T::from_str(&value, true)
+                .map_err(anyhow::Error::msg)
+                .with_context(|| format!("invalid value for {key}"))
+        })
+        .transpose()
+}
+
+/// The name of `value` on the command line, like `months`
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRepo;
+
+    fn load(repo: &TestRepo, args: &[&str]) -> Result<(Config, GitGardenerArgs)> {
+        let matches = repo.matches(args);
+        let mut args = repo.args(args);
+        let config = Config::load(&repo.repository)?.with_args(&args, &matches);
+        config.apply(&mut args);
+        Ok((config, args))
+    }
+
+    #[test]
+    fn test_defaults() -> Result<()> {
+        let repo = TestRepo::new()?;
+
+        let (config, args) = load(&repo, &["steleness"])?;
+
+        assert_eq!(config, Config::default());
+        assert_eq!(args.main_branch, "main");
+        assert!(matches!(
+            args.command,
+            Some(StalenessDate::Steleness {
+                number: 3,
+                period: PeriodArgs::Months
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_git_config_and_command_line() -> Result<()> {
+        let repo = TestRepo::new()?;
+        let path = repo.dir.path().join(FILE_NAME);
+        fs::write(
+            &path,
+            "main_branch = \"develop\"\nprotect = [\"release/*\"]\nformat = \"json\"\n\n\
+             [staleness]\nnumber = 6\nperiod = \"days\"\n",
+        )?;
+        let mut git_config = repo.repository.config()?;
+        git_config.set_str(MAIN_BRANCH_KEY, "trunk")?;
+        git_config.set_str(STALENESS_PERIOD_KEY, "Months")?;
+        git_config.set_multivar(protection::CONFIG_KEY, "^$", "hotfix/*")?;
+
+        let (config, args) = load(&repo, &["-P", "keep", "--format", "text", "steleness"])?;
+
+        let path = Source::File(path);
+        assert_eq!(
+            config.main_branch,
+            Setting::new("trunk".to_string(), Source::GitConfig(MAIN_BRANCH_KEY))
+        );
+        assert_eq!(config.staleness_number, Setting::new(6, path.clone()));
+        assert_eq!(
+            config.staleness_period,
+            Setting::new(PeriodArgs::Months, Source::GitConfig(STALENESS_PERIOD_KEY))
+        );
+        assert_eq!(
+            config.format,
+            Setting::new(OutputFormat::Text, Source::CommandLine)
+        );
+        assert_eq!(
+            config.protect,
+            vec![
+                Setting::new("keep".to_string(), Source::CommandLine),
+                Setting::new("release/*".to_string(), path),
+                Setting::new(
+                    "hotfix/*".to_string(),
+                    Source::GitConfig(protection::CONFIG_KEY)
+                ),
+            ]
+        );
+        assert_eq!(args.main_branch, "trunk");
+        assert_eq!(args.protect, vec!["keep", "release/*"]);
+        assert!(matches!(
+            args.command,
+            Some(StalenessDate::Steleness {
+                number: 6,
+                period: PeriodArgs::Months
+            })
+        ));
+
+        let (config, args) = load(&repo, &["-m", "main", "steleness", "-n", "1"])?;
+        assert_eq!(config.main_branch.source, Source::CommandLine);
+        assert_eq!(config.format.value, OutputFormat::Json);
+        assert_eq!(args.main_branch, "main");
+        assert!(matches!(
+            args.command,
+            Some(StalenessDate::Steleness { number: 1, .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_configuration() -> Result<()> {
+        let repo = TestRepo::new()?;
+        fs::write(repo.dir.path().join(FILE_NAME), "main = \"develop\"\n")?;
+        assert!(Config::load(&repo.repository).is_err());
+
+        fs::remove_file(repo.dir.path().join(FILE_NAME))?;
+        repo.repository
+            .config()?
+            .set_str(STALENESS_PERIOD_KEY, "weeks")?;
+        let err = Config::load(&repo.repository).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value for gitgardener.stalenessPeriod"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_show() -> Result<()> {
+        let repo = TestRepo::new()?;
+        repo.repository
+            .config()?
+            .set_str(STALENESS_NUMBER_KEY, "12")?;
+        let (config, _) = load(&repo, &["-P", "release/*", "config", "show"])?;
+        let mut output = Vec::new();
+
+        config.show(&mut output)?;
+
+        assert_eq!(
+            String::from_utf8(output)?,
+            "main_branch = main         (default)\n\
+             staleness.number = 12      (git config gitgardener.stalenessNumber)\n\
+             staleness.period = months  (default)\n\
+             format = text              (default)\n\
+             protect = release/*        (command line)\n"
+        );
+        Ok(())
+    }
+}
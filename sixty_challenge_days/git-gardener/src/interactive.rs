@@ -0,0 +1,127 @@
+use anyhow::Result;
+use dialoguer::{theme::ColorfulTheme, Confirm, MultiSelect};
+
+use crate::candidate::Candidate;
+
+/// Lets the user toggle which of the `candidates` to delete and confirm the deletion.
+///
+/// Returns the chosen candidates, plus the protected ones so they are still reported, or
+/// `None` when the user gives up. A dry run is not confirmed, as it deletes nothing.
+pub fn select(candidates: Vec<Candidate>, dry_run: bool) -> Result<Option<Vec<Candidate>>> {
+    let (protected, deletable): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|candidate| candidate.protected.is_some());
+    if deletable.is_empty() {
+        return Ok(Some(protected));
+    }
+
+    let theme = ColorfulTheme::default();
+    let Some(chosen) = MultiSelect::with_theme(&theme)
+        .with_prompt("Branches to delete (space toggles, enter accepts, esc quits)")
+        .items(&rows(&deletable))
+        .defaults(&vec![true; deletable.len()])
+        .interact_opt()?
+    else {
+        return Ok(None);
+    };
+    if !dry_run
+        && !chosen.is_empty()
+        && !Confirm::with_theme(&theme)
+            .with_prompt(format!("Delete {} branches?", chosen.len()))
+            .default(false)
+            .interact()?
+    {
+        return Ok(None);
+    }
+
+    let mut selected: Vec<_> = deletable
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| chosen.contains(index))
+        .map(|(_, candidate)| candidate)
+        .collect();
+    selected.extend(protected);
+    Ok(Some(selected))
+}
+
+/// One line per candidate, with the name, last commit date, author, commits ahead and
+/// behind the main branch and merge status in aligned columns
+fn rows(candidates: &[Candidate]) -> Vec<String> {
+    let columns: Vec<[String; 5]> = candidates
+        .iter()
+        .map(|candidate| {
+            [
+                candidate.name.clone(),
+                candidate
+                    .last_commit
+                    .map(|date| date.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default(),
+                candidate.author.clone(),
+                candidate
+                    .ahead_behind
+                    .map(|(ahead, behind)| format!("+{ahead} -{behind}"))
+                    .unwrap_or_default(),
+                candidate
+                    .merge_status
+                    .map(|status| status.to_string())
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    let mut widths = [0; 5];
+    for row in &columns {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.chars().count());
+        }
+    }
+
+    columns
+        .iter()
+        .map(|row| {
+            let line: Vec<_> = row
+                .iter()
+                .zip(widths)
+                .map(|(column, width)| format!("{column:width$}"))
+                .collect();
+            line.join("  ").trim_end().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merged::MergeStatus;
+    use chrono::{Local, TimeZone};
+
+    fn candidate(name: &str, author: &str, ahead_behind: (usize, usize)) -> Candidate {
+        Candidate {
+            name: name.to_string(),
+            remote: None,
+            last_commit: Local.with_ymd_and_hms(2024, 3, 7, 12, 0, 0).single(),
+            author: author.to_string(),
+            ahead_behind: Some(ahead_behind),
+            merge_status: Some(MergeStatus::NotMerged),
+            protected: None,
+        }
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut squashed = candidate("feature/squashed", "Ana", (12, 3));
+        squashed.merge_status = Some(MergeStatus::Squashed);
+        let mut unknown = candidate("fix", "Bia Souza", (0, 0));
+        unknown.ahead_behind = None;
+        unknown.merge_status = None;
+
+        assert_eq!(
+            rows(&[squashed, candidate("old", "Caio", (1, 40)), unknown]),
+            vec![
+                "feature/squashed  2024-03-07  Ana        +12 -3  squash merged",
+                "old               2024-03-07  Caio       +1 -40  not merged",
+                "fix               2024-03-07  Bia Souza",
+            ]
+        );
+    }
+}
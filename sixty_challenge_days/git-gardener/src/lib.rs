@@ -1,10 +1,22 @@
 mod arg;
 mod branch;
+mod candidate;
+mod config;
+mod interactive;
+mod merged;
+mod protection;
+mod remote;
+#[cfg(test)]
+mod testing;
+
+use std::io;
 
 use anyhow::{Ok, Result};
-use arg::{GitGardenerArgs, StalenessDate};
-use branch::Branch;
+use arg::{ConfigCommand, GitGardenerArgs, StalenessDate};
+use branch::{Branch, Report};
+use candidate::{delete_candidates, describe_candidates, Candidate};
 use clap::{CommandFactory, FromArgMatches};
+use config::Config;
 use git2::Repository;
 
 pub struct GitGardener {
@@ -12,6 +24,12 @@ pub struct GitGardener {
     pub branch: Option<Branch>,
 }
 
+impl Default for GitGardener {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GitGardener {
     pub fn new() -> Self {
         Self {
@@ -21,10 +39,20 @@ impl GitGardener {
     }
     pub fn parse_args_and_run(mut self) -> Result<()> {
         let matches = GitGardenerArgs::command().get_matches();
-        let args = GitGardenerArgs::from_arg_matches(&matches)
+        let mut args = GitGardenerArgs::from_arg_matches(&matches)
             .expect("args to already to parsed successfully");
 
-        self.repository = Some(git2::Repository::open(&args.git_repository)?);
+        let repository = git2::Repository::open(&args.git_repository)?;
+        let config = Config::load(&repository)?.with_args(&args, &matches);
+        config.apply(&mut args);
+        self.repository = Some(repository);
+
+        if let Some(StalenessDate::Config {
+            command: ConfigCommand::Show,
+        }) = args.command
+        {
+            return config.show(&mut io::stdout());
+        }
 
         if matches!(args.command, Some(StalenessDate::Steleness { .. })) {
             self.branch = Branch::load_from_args(&args.command);
@@ -34,10 +62,35 @@ impl GitGardener {
         Ok(())
     }
 
+    /// The branches matching the cleanup criteria of the command, the protected ones
+    /// included, or `None` when there is no cleanup to do.
+    pub fn candidates(&self, args: &GitGardenerArgs) -> Result<Option<Vec<Candidate>>> {
+        let candidates = match (&args.command, &self.branch) {
+            (Some(StalenessDate::Merged), _) => merged::merged_candidates(args, self)?,
+            (Some(StalenessDate::Gone), _) => remote::gone_candidates(args, self)?,
+            (_, Some(branch)) => branch.steleness_candidates(args, self)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(candidates))
+    }
+
     pub fn run(&self, args: GitGardenerArgs) -> Result<()> {
-        self.branch
-            .as_ref()
-            .and_then(|branch| branch.delete_steleness_branches(&args, &self).ok());
+        let Some(mut candidates) = self.candidates(&args)? else {
+            return Ok(());
+        };
+        if args.interactive {
+            describe_candidates(&args, branch::repository(self), &mut candidates)?;
+            let Some(selected) = interactive::select(candidates, args.dry_run)? else {
+                // Keeps the output parseable with `--format json`
+                eprintln!("Nothing was deleted");
+                Report::default().print(args.dry_run, args.format);
+                return Ok(());
+            };
+            candidates = selected;
+        }
+
+        let report = delete_candidates(&args, branch::repository(self), candidates)?;
+        report.print(args.dry_run, args.format);
         Ok(())
     }
 }
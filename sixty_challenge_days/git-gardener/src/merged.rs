@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{Context, Result};
+use git2::{BranchType, Commit, Oid, Repository};
+
+use crate::arg::GitGardenerArgs;
+use crate::branch::repository;
+use crate::candidate::{find_candidates, Candidate};
+use crate::GitGardener;
+
+/// How the changes of a branch got into the main branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStatus {
+    /// The branch tip is part of the main branch history
+    Merged,
+    /// The branch changes were added to the main branch by other commits
+    Squashed,
+    NotMerged,
+}
+
+impl fmt::Display for MergeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeStatus::Merged => write!(f, "merged"),
+            MergeStatus::Squashed => write!(f, "squash merged"),
+            MergeStatus::NotMerged => write!(f, "not merged"),
+        }
+    }
+}
+
+impl MergeStatus {
+    pub fn is_merged(self) -> bool {
+        self != MergeStatus::NotMerged
+    }
+}
+
+/// Finds out whether branches were merged into the branch at `main`.
+///
+/// The patch-id of each commit of `main` is computed at most once, however many branches
+/// are checked, as it takes a diff of the whole commit.
+pub struct MergeCheck<'r> {
+    repository: &'r Repository,
+    main: Commit<'r>,
+    /// Patch-id of each commit of `main` looked at so far, `None` for merge commits
+    patch_ids: HashMap<Oid, Option<Oid>>,
+}
+
+impl<'r> MergeCheck<'r> {
+    pub fn new(repository: &'r Repository, main: Commit<'r>) -> Self {
+        Self {
+            repository,
+            main,
+            patch_ids: HashMap::new(),
+        }
+    }
+
+    /// Finds out whether the branch at `tip` was merged into `main`.
+    ///
+    /// A branch that is not in the `main` history is still squash merged when either:
+    /// * a commit of `main` since the merge base has the same patch-id as the whole branch,
+    ///   or
+    /// * merging the branch into `main` would not change the `main` tree.
+    pub fn status(&mut self, tip: &Commit) -> Result<MergeStatus> {
+        let repository = self.repository;
+        let main = self.main.id();
+        if tip.id() == main || repository.graph_descendant_of(main, tip.id())? {
+            return Ok(MergeStatus::Merged);
+        }
+        let base = match repository.merge_base(main, tip.id()) {
+            Ok(base) => repository.find_commit(base)?,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                return Ok(MergeStatus::NotMerged)
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let branch_patch = patch_id(repository, &base, tip)?;
+        let mut walk = repository.revwalk()?;
+        walk.push(main)?;
+        walk.hide(base.id())?;
+        for oid in walk {
+            if self.patch_id_of(oid?)? == Some(branch_patch) {
+                return Ok(MergeStatus::Squashed);
+            }
+        }
+
+        let main_tree = self.main.tree()?;
+        let merged = repository.merge_trees(&base.tree()?, &main_tree, &tip.tree()?, None)?;
+        if !merged.has_conflicts() {
+            let diff = repository.diff_tree_to_index(Some(&main_tree), Some(&merged), None)?;
+            if diff.deltas().len() == 0 {
+                return Ok(MergeStatus::Squashed);
+            }
+        }
+
+        Ok(MergeStatus::NotMerged)
+    }
+
+    pub fn main_id(&self) -> Oid {
+        self.main.id()
+    }
+
+    /// Patch-id of the changes of the commit `oid` of `main`, `None` for a merge commit
+    fn patch_id_of(&mut self, oid: Oid) -> Result<Option<Oid>> {
+        if let Some(patch) = self.patch_ids.get(&oid) {
+            return Ok(*patch);
+        }
+        let commit = self.repository.find_commit(oid)?;
+        let patch = match commit.parent_count() {
+            1 => Some(patch_id(self.repository, &commit.parent(0)?, &commit)?),
+            _ => None,
+        };
+        self.patch_ids.insert(oid, patch);
+        Ok(patch)
+    }
+}
+
+/// Patch-id of the changes from `old` to `new`, which ignores line numbers and whitespace
+fn patch_id(repository: &Repository, old: &Commit, new: &Commit) -> Result<Oid> {
+    let diff = repository.diff_tree_to_tree(Some(&old.tree()?), Some(&new.tree()?), None)?;
+    Ok(diff.patchid(None)?)
+}
+
+/// The last commit of `main_branch`, or of its remote-tracking branch on `remote`
+pub fn main_commit<'r>(
+    repository: &'r Repository,
+    main_branch: &str,
+    remote: Option<&str>,
+) -> Result<Commit<'r>> {
+    let (name, branch_type) = match remote {
+        Some(remote) => (format!("{remote}/{main_branch}"), BranchType::Remote),
+        None => (main_branch.to_string(), BranchType::Local),
+    };
+    let main = repository
+        .find_branch(&name, branch_type)
+        .with_context(|| format!("main branch `{name}` not found"))?;
+    Ok(main.get().peel_to_commit()?)
+}
+
+/// The branches merged into the main branch, with their merge status.
+pub fn merged_candidates(args: &GitGardenerArgs, gardener: &GitGardener) -> Result<Vec<Candidate>> {
+    let repository = repository(gardener);
+    let main = main_commit(repository, &args.main_branch, args.remote.as_deref())?;
+    let mut check = MergeCheck::new(repository, main);
+    let mut statuses = HashMap::new();
+
+    let mut candidates = find_candidates(args, repository, |branch| {
+        let tip = branch.get().peel_to_commit()?;
+        let status = check.status(&tip)?;
+        if let Some(name) = branch.name()? {
+            statuses.insert(name.to_string(), status);
+        }
+        Ok(status.is_merged())
+    })?;
+    // Kept so `--interactive` does not have to find it out again
+    for candidate in &mut candidates {
+        candidate.merge_status = statuses.remove(&candidate.name);
+    }
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::candidate::delete_candidates;
+    use crate::protection::Protected;
+    use crate::testing::TestRepo;
+
+    /// `main` with one branch merged, two squash merged and one not merged yet
+    fn merged_repo() -> Result<TestRepo> {
+        let repo = TestRepo::new()?;
+        repo.branch("feature/open", 10)?;
+        repo.branch("feature/squashed", 10)?;
+        repo.commit("feature/squashed", "second", 9)?;
+        repo.branch("feature/amended", 8)?;
+        let merged = repo.branch("feature/merged", 7)?;
+        repo.repository
+            .reference("refs/heads/main", merged, true, "fast-forward")?;
+        repo.commit_files("main", &["feature-squashed", "second"], 5)?;
+        repo.commit_files("main", &["feature-amended", "unrelated"], 4)?;
+        Ok(repo)
+    }
+
+    fn status(repo: &TestRepo, name: &str) -> Result<MergeStatus> {
+        let main = main_commit(&repo.repository, "main", None)?;
+        let tip = repo.repository.revparse_single(name)?.peel_to_commit()?;
+        MergeCheck::new(&repo.repository, main).status(&tip)
+    }
+
+    #[test]
+    fn test_merge_status() -> Result<()> {
+        let repo = merged_repo()?;
+
+        assert_eq!(status(&repo, "main")?, MergeStatus::Merged);
+        assert_eq!(status(&repo, "feature/merged")?, MergeStatus::Merged);
+        assert_eq!(status(&repo, "feature/squashed")?, MergeStatus::Squashed);
+        assert_eq!(status(&repo, "feature/amended")?, MergeStatus::Squashed);
+        assert_eq!(status(&repo, "feature/open")?, MergeStatus::NotMerged);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_check_reuses_patch_ids() -> Result<()> {
+        let repo = merged_repo()?;
+        let main = main_commit(&repo.repository, "main", None)?;
+        let mut check = MergeCheck::new(&repo.repository, main);
+
+        for name in ["feature/open", "feature/amended", "feature/squashed"] {
+            let tip = repo.repository.revparse_single(name)?.peel_to_commit()?;
+            check.status(&tip)?;
+        }
+
+        // The three commits of main since the branches were created, each one diffed once
+        assert_eq!(check.patch_ids.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_merged_branches() -> Result<()> {
+        let repo = merged_repo()?;
+
+        for dry_run in [true, false] {
+            let mut args = repo.args(&["merged"]);
+            args.dry_run = dry_run;
+            let gardener = GitGardener {
+                repository: Some(Repository::open(repo.dir.path())?),
+                branch: None,
+            };
+
+            let candidates = merged_candidates(&args, &gardener)?;
+            let statuses: Vec<_> = candidates
+                .iter()
+                .map(|candidate| candidate.merge_status)
+                .collect();
+            assert_eq!(
+                statuses,
+                vec![
+                    Some(MergeStatus::Squashed),
+                    Some(MergeStatus::Merged),
+                    Some(MergeStatus::Squashed),
+                    Some(MergeStatus::Merged),
+                ]
+            );
+            let report = delete_candidates(&args, repository(&gardener), candidates)?;
+
+            assert_eq!(
+                report.deleted,
+                vec!["feature/amended", "feature/merged", "feature/squashed"]
+            );
+            assert_eq!(report.skipped, vec![("main".to_string(), Protected::Head)]);
+        }
+        assert_eq!(repo.branches()?, vec!["feature/open", "main"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_main_branch() -> Result<()> {
+        let repo = TestRepo::new()?;
+        let args = repo.args(&["--main-branch", "trunk", "merged"]);
+        let gardener = GitGardener {
+            repository: Some(Repository::open(repo.dir.path())?),
+            branch: None,
+        };
+
+        let err = merged_candidates(&args, &gardener).unwrap_err();
+
+        assert_eq!(err.to_string(), "main branch `trunk` not found");
+        Ok(())
+    }
+}
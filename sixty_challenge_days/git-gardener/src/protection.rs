@@ -0,0 +1,165 @@
+use std::fmt;
+
+use anyhow::{Context, Result};
+use git2::Repository;
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::arg::GitGardenerArgs;
+
+/// Git config key with extra protected patterns, one per entry
+pub const CONFIG_KEY: &str = "gitgardener.protect";
+
+/// Why a branch is never deleted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Protected {
+    /// The branch checked out in the repository
+    Head,
+    MainBranch,
+    /// The branch name matches this protected pattern
+    Pattern(String),
+}
+
+impl fmt::Display for Protected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protected::Head => write!(f, "it is checked out (HEAD)"),
+            Protected::MainBranch => write!(f, "it is the main branch"),
+            Protected::Pattern(pattern) => {
+                write!(f, "it matches the protected pattern `{pattern}`")
+            }
+        }
+    }
+}
+
+/// Decides which branches are protected, in every cleanup mode and also in dry runs
+#[derive(Debug)]
+pub struct Protection {
+    main_branch: String,
+    patterns: Vec<(String, GlobMatcher)>,
+}
+
+impl Protection {
+    /// Protects `main_branch` and the branches matching any of the glob `patterns`, where
+    /// `*` does not cross a `/`.
+    pub fn new(main_branch: &str, patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                let glob = GlobBuilder::new(pattern)
+                    .literal_separator(true)
+                    .build()
+                    .with_context(|| format!("invalid protected pattern `{pattern}`"))?;
+                Ok((pattern.clone(), glob.compile_matcher()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            main_branch: main_branch.to_string(),
+            patterns,
+        })
+    }
+
+    /// Combines the `--protect` patterns with the ones in the `gitgardener.protect` entries
+    /// of the repository config.
+    pub fn load(args: &GitGardenerArgs, repository: &Repository) -> Result<Self> {
+        let mut patterns = args.protect.clone();
+        let config = repository.config()?;
+        let mut entries = match config.multivar(CONFIG_KEY, None) {
+            Ok(entries) => entries,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {
+                return Self::new(&args.main_branch, &patterns)
+            }
+            Err(err) => return Err(err.into()),
+        };
+        while let Some(entry) = entries.next() {
+            if let Some(pattern) = entry?.value() {
+                patterns.push(pattern.to_string());
+            }
+        }
+
+        Self::new(&args.main_branch, &patterns)
+    }
+
+    /// Why `branch` must be kept, or `None` if it may be deleted.
+    pub fn check(&self, branch: &git2::Branch) -> Option<Protected> {
+        if branch.is_head() {
+            return Some(Protected::Head);
+        }
+        branch
+            .name()
+            .ok()
+            .flatten()
+            .and_then(|name| self.check_name(name))
+    }
+
+    /// Like [`Protection::check`], by the short name of a branch that is not checked out.
+    pub fn check_name(&self, name: &str) -> Option<Protected> {
+        if name == self.main_branch {
+            return Some(Protected::MainBranch);
+        }
+        self.patterns
+            .iter()
+            .find(|(_, glob)| glob.is_match(name))
+            .map(|(pattern, _)| Protected::Pattern(pattern.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRepo;
+
+    #[test]
+    fn test_check_name() -> Result<()> {
+        let protection =
+            Protection::new("main", &["release/*".to_string(), "develop".to_string()])?;
+
+        assert_eq!(protection.check_name("main"), Some(Protected::MainBranch));
+        assert_eq!(
+            protection.check_name("release/1.0"),
+            Some(Protected::Pattern("release/*".to_string()))
+        );
+        assert_eq!(
+            protection.check_name("develop"),
+            Some(Protected::Pattern("develop".to_string()))
+        );
+        assert_eq!(protection.check_name("release/1.0/fix"), None);
+        assert_eq!(protection.check_name("feature/develop"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(Protection::new("main", &["release/[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_load_patterns_from_git_config() -> Result<()> {
+        let repo = TestRepo::new()?;
+        let mut config = repo.repository.config()?;
+        config.set_multivar(CONFIG_KEY, "^$", "hotfix/*")?;
+        config.set_multivar(CONFIG_KEY, "^$", "develop")?;
+        let args = repo.args(&["--protect", "release/*"]);
+
+        let protection = Protection::load(&args, &repo.repository)?;
+
+        for name in ["release/2.0", "hotfix/urgent", "develop"] {
+            assert!(protection.check_name(name).is_some(), "{name}");
+        }
+        assert_eq!(protection.check_name("feature/x"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_head() -> Result<()> {
+        let repo = TestRepo::new()?;
+        let protection = Protection::new("trunk", &[])?;
+
+        let head = repo
+            .repository
+            .find_branch("main", git2::BranchType::Local)?;
+
+        assert_eq!(protection.check(&head), Some(Protected::Head));
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::{bail, Context, Result};
+use git2::{BranchType, Cred, CredentialType, PushOptions, RemoteCallbacks, Repository};
+
+use crate::arg::GitGardenerArgs;
+use crate::branch::repository;
+use crate::candidate::{check_remote, find_local_candidates, Candidate};
+use crate::GitGardener;
+
+/// Deletes the `branches` from `remote` with a push, and their remote-tracking branches.
+pub fn delete_remote_branches(
+    repository: &Repository,
+    remote: &str,
+    branches: &[String],
+) -> Result<()> {
+    push_deletions(repository, remote, branches)?;
+    for branch in branches {
+        // The push may have removed the remote-tracking branch already
+        if let Ok(mut branch) =
+            repository.find_branch(&format!("{remote}/{branch}"), BranchType::Remote)
+        {
+            branch.delete()?;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the `branches` from `remote` with a single push.
+fn push_deletions(repository: &Repository, remote: &str, branches: &[String]) -> Result<()> {
+    let mut remote_repository = repository.find_remote(remote)?;
+    let refspecs: Vec<_> = branches
+        .iter()
+        .map(|branch| format!(":refs/heads/{branch}"))
+        .collect();
+    let config = repository.config()?;
+    let mut rejected = Vec::new();
+
+    {
+        // libgit2 asks again after a refused credential, so only offer one
+        let mut tried = false;
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.credentials(|url, username, allowed| {
+            let username = username.unwrap_or("git");
+            if allowed.contains(CredentialType::USERNAME) {
+                return Cred::username(username);
+            }
+            if std::mem::replace(&mut tried, true) {
+                return Err(git2::Error::from_str("authentication failed"));
+            }
+            if allowed.contains(CredentialType::SSH_KEY) {
+                Cred::ssh_key_from_agent(username)
+            } else {
+                Cred::credential_helper(&config, url, Some(username))
+            }
+        });
+        callbacks.push_update_reference(|reference, status| {
+            if let Some(message) = status {
+                rejected.push(format!("{reference} ({message})"));
+            }
+            Ok(())
+        });
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+
+        remote_repository
+            .push(&refspecs, Some(&mut options))
+            .with_context(|| format!("could not push to the remote `{remote}`"))?;
+    }
+
+    if !rejected.is_empty() {
+        bail!(
+            "the remote `{remote}` refused to delete {}",
+            rejected.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Whether `branch` has an upstream branch, on `remote` when given, that no longer exists.
+pub fn upstream_gone(
+    repository: &Repository,
+    branch: &git2::Branch,
+    remote: Option<&str>,
+) -> Result<bool> {
+    let Some(reference) = branch.get().name() else {
+        return Ok(false);
+    };
+    if let Some(remote) = remote {
+        match repository.branch_upstream_remote(reference) {
+            Ok(upstream_remote) if upstream_remote.as_str() == Some(remote) => {}
+            Ok(_) => return Ok(false),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(false),
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let upstream = match repository.branch_upstream_name(reference) {
+        Ok(upstream) => upstream,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    let Some(upstream) = upstream.as_str() else {
+        return Ok(false);
+    };
+    match repository.find_reference(upstream) {
+        Ok(_) => Ok(false),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(true),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// The local branches whose upstream branch is gone.
+pub fn gone_candidates(args: &GitGardenerArgs, gardener: &GitGardener) -> Result<Vec<Candidate>> {
+    let repository = repository(gardener);
+    if let Some(remote) = &args.remote {
+        check_remote(repository, remote)?;
+    }
+
+    find_local_candidates(args, repository, |branch| {
+        upstream_gone(repository, branch, args.remote.as_deref())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::branch::Report;
+    use crate::candidate::{delete_candidates, find_candidates};
+    use crate::merged::merged_candidates;
+    use crate::protection::Protected;
+    use crate::testing::TestRepo;
+
+    fn gardener(repo: &TestRepo) -> Result<GitGardener> {
+        Ok(GitGardener {
+            repository: Some(Repository::open(repo.dir.path())?),
+            branch: None,
+        })
+    }
+
+    #[test]
+    fn test_delete_remote_merged_branches() -> Result<()> {
+        let repo = TestRepo::new()?;
+        repo.branch("feature/open", 10)?;
+        let merged = repo.branch("feature/merged", 8)?;
+        repo.repository
+            .reference("refs/heads/main", merged, true, "fast-forward")?;
+        repo.branch("release/1.0", 5)?;
+        repo.repository
+            .reference("refs/heads/release/1.0", merged, true, "reset")?;
+        let origin = repo.remote("origin")?;
+
+        for dry_run in [true, false] {
+            let mut args = repo.args(&["--remote", "origin", "-P", "release/*", "merged"]);
+            args.dry_run = dry_run;
+
+            let candidates = merged_candidates(&args, &gardener(&repo)?)?;
+            let report = delete_candidates(&args, &repo.repository, candidates)?;
+
+            assert_eq!(report.deleted, vec!["origin/feature/merged"]);
+            assert_eq!(
+                report.skipped,
+                vec![
+                    ("origin/main".to_string(), Protected::MainBranch),
+                    (
+                        "origin/release/1.0".to_string(),
+                        Protected::Pattern("release/*".to_string())
+                    ),
+                ]
+            );
+        }
+        assert_eq!(
+            origin.branches()?,
+            vec!["feature/open", "main", "release/1.0"]
+        );
+        assert_eq!(
+            repo.remote_branches()?,
+            vec!["origin/feature/open", "origin/main", "origin/release/1.0"]
+        );
+        assert_eq!(
+            repo.branches()?,
+            vec!["feature/merged", "feature/open", "main", "release/1.0"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_remote() -> Result<()> {
+        let repo = TestRepo::new()?;
+        let args = repo.args(&["--remote", "upstream", "--dry-run"]);
+
+        let err = find_candidates(&args, &repo.repository, |_| Ok(true)).unwrap_err();
+        assert_eq!(err.to_string(), "remote `upstream` not found");
+
+        let args = repo.args(&["--remote", "upstream", "gone"]);
+        let err = gone_candidates(&args, &gardener(&repo)?).unwrap_err();
+        assert_eq!(err.to_string(), "remote `upstream` not found");
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_gone_branches() -> Result<()> {
+        let repo = TestRepo::new()?;
+        for name in ["feature/gone", "feature/kept", "feature/local"] {
+            repo.branch(name, 3)?;
+        }
+        repo.remote("origin")?;
+        repo.remote("upstream")?;
+        for name in ["feature/gone", "feature/kept"] {
+            repo.repository
+                .find_branch(name, BranchType::Local)?
+                .set_upstream(Some(&format!("origin/{name}")))?;
+        }
+        // What `git fetch --prune` does once the branch is deleted on the remote
+        repo.repository
+            .find_branch("origin/feature/gone", BranchType::Remote)?
+            .delete()?;
+
+        let args = repo.args(&["--remote", "upstream", "gone"]);
+        let candidates = gone_candidates(&args, &gardener(&repo)?)?;
+        let report = delete_candidates(&args, &repo.repository, candidates)?;
+        assert_eq!(report, Report::default());
+
+        let args = repo.args(&["gone"]);
+        let candidates = gone_candidates(&args, &gardener(&repo)?)?;
+        let report = delete_candidates(&args, &repo.repository, candidates)?;
+        assert_eq!(report.deleted, vec!["feature/gone"]);
+        assert_eq!(
+            repo.branches()?,
+            vec!["feature/kept", "feature/local", "main"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+//! Throwaway repositories for the tests
+use anyhow::Result;
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use git2::{Oid, Repository, Signature, Time};
+use tempfile::TempDir;
+
+use crate::arg::GitGardenerArgs;
+
+const DAY: i64 = 24 * 60 * 60;
+
+pub struct TestRepo {
+    pub dir: TempDir,
+    pub repository: Repository,
+}
+
+impl TestRepo {
+    /// A repository with `main` checked out and a first commit made 400 days ago.
+    pub fn new() -> Result<Self> {
+        let dir = TempDir::new()?;
+        let repository = Repository::init(dir.path())?;
+        repository.set_head("refs/heads/main")?;
+        {
+            let tree = repository.treebuilder(None)?.write()?;
+            let tree = repository.find_tree(tree)?;
+            let signature = signature(400)?;
+            repository.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "first commit",
+                &tree,
+                &[],
+            )?;
+        }
+
+        Ok(Self { dir, repository })
+    }
+
+    /// Creates `name` from `main` with one commit, made `days_ago`, that adds `file`.
+    pub fn branch(&self, name: &str, days_ago: i64) -> Result<Oid> {
+        let main = self.repository.revparse_single("main")?.peel_to_commit()?;
+        self.repository.branch(name, &main, false)?;
+        self.commit(name, &name.replace('/', "-"), days_ago)
+    }
+
+    /// Commits on the branch `name` a file called `file`, `days_ago`.
+    pub fn commit(&self, name: &str, file: &str, days_ago: i64) -> Result<Oid> {
+        self.commit_files(name, &[file], days_ago)
+    }
+
+    /// Commits on the branch `name` the `files`, each with its own name as content,
+    /// `days_ago`.
+    pub fn commit_files(&self, name: &str, files: &[&str], days_ago: i64) -> Result<Oid> {
+        let parent = self
+            .repository
+            .revparse_single(&format!("refs/heads/{name}"))?
+            .peel_to_commit()?;
+        let mut tree = self.repository.treebuilder(Some(&parent.tree()?))?;
+        for file in files {
+            let blob = self.repository.blob(file.as_bytes())?;
+            tree.insert(file, blob, 0o100644)?;
+        }
+        let tree = self.repository.find_tree(tree.write()?)?;
+        let signature = signature(days_ago)?;
+
+        Ok(self.repository.commit(
+            Some(&format!("refs/heads/{name}")),
+            &signature,
+            &signature,
+            &format!("add {}", files.join(", ")),
+            &tree,
+            &[&parent],
+        )?)
+    }
+
+    /// Local branch names, sorted.
+    pub fn branches(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for branch in self.repository.branches(Some(git2::BranchType::Local))? {
+            if let Some(name) = branch?.0.name()? {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Creates a bare repository as the remote `name`, pushes every local branch to it and
+    /// returns it.
+    pub fn remote(&self, name: &str) -> Result<TestRepo> {
+        let dir = TempDir::new()?;
+        let repository = Repository::init_bare(dir.path())?;
+        let url = dir.path().to_str().expect("temporary dir is UTF-8");
+        let mut remote = self.repository.remote(name, url)?;
+        let refspecs: Vec<_> = self
+            .branches()?
+            .iter()
+            .map(|branch| format!("refs/heads/{branch}:refs/heads/{branch}"))
+            .collect();
+        remote.push(&refspecs, None)?;
+        remote.fetch(&[] as &[&str], None, None)?;
+
+        Ok(TestRepo { dir, repository })
+    }
+
+    /// Remote-tracking branch names, sorted.
+    pub fn remote_branches(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for branch in self.repository.branches(Some(git2::BranchType::Remote))? {
+            if let Some(name) = branch?.0.name()? {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Parses the command line `args` for this repository.
+    pub fn args(&self, args: &[&str]) -> GitGardenerArgs {
+        GitGardenerArgs::from_arg_matches(&self.matches(args)).expect("valid arguments")
+    }
+
+    /// Like [`TestRepo::args`], keeping what clap knows about each argument.
+    pub fn matches(&self, args: &[&str]) -> ArgMatches {
+        let repository = self.dir.path().to_str().expect("temporary dir is UTF-8");
+        GitGardenerArgs::command()
+            .get_matches_from(["git-gardener", "-g", repository].iter().chain(args))
+    }
+}
+
+fn signature(days_ago: i64) -> Result<Signature<'static>> {
+    let now = chrono::Local::now().timestamp();
+    Ok(Signature::new(
+        "Gardener",
+        "gardener@example.com",
+        &Time::new(now - days_ago * DAY, 0),
+    )?)
+}